@@ -0,0 +1,179 @@
+// On-disk cache of per-interpreter package inventories. Entries are keyed by the
+// interpreter path and remember the site-packages fingerprint they were read at
+// (see `site_packages::fingerprint`), so startup can show the last known lists
+// straight away and only re-read the interpreters that actually changed. A cache
+// written with another schema is dropped and filled again, since it is only a cache.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::Result;
+
+// Stored as SQLite's `user_version`; bump it whenever the table changes
+const SCHEMA_VERSION: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    // Loaded from disk and not yet revalidated
+    Cached,
+    // Being re-read in the background
+    Refreshing,
+    // Read (or revalidated) during this session
+    Fresh,
+}
+
+impl Freshness {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Freshness::Cached => "cached",
+            Freshness::Refreshing => "refreshing",
+            Freshness::Fresh => "fresh",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    pub packages: Vec<String>,
+    pub site_packages_fingerprint: Option<i64>,
+    pub freshness: Freshness,
+}
+
+pub struct InventoryCache {
+    conn: Mutex<Connection>,
+}
+
+impl InventoryCache {
+    pub fn open(path: &Path) -> Result<InventoryCache> {
+        if let Some(parent) = path.parent() {
+            // A missing directory surfaces as an open error below
+            let _ = fs::create_dir_all(parent);
+        }
+        let conn = Connection::open(path)?;
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            conn.execute_batch(&format!("DROP TABLE IF EXISTS inventories; PRAGMA user_version = {};", SCHEMA_VERSION))?;
+        }
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS inventories (
+                interpreter               TEXT PRIMARY KEY,
+                site_packages_fingerprint INTEGER,
+                packages                  TEXT NOT NULL,
+                updated_at                INTEGER NOT NULL
+            );",
+        )?;
+        Ok(InventoryCache { conn: Mutex::new(conn) })
+    }
+
+    // The stored inventory for an interpreter, marked as not yet revalidated
+    pub fn load(&self, interpreter: &str) -> Result<Option<Inventory>> {
+        let conn = self.conn.lock().unwrap();
+        let row = conn
+            .query_row(
+                "SELECT site_packages_fingerprint, packages FROM inventories WHERE interpreter = ?1",
+                params![interpreter],
+                |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+
+        Ok(row.and_then(|(site_packages_fingerprint, packages)| {
+            let packages: Vec<String> = serde_json::from_str(&packages).ok()?;
+            Some(Inventory {
+                packages,
+                site_packages_fingerprint,
+                freshness: Freshness::Cached,
+            })
+        }))
+    }
+
+    pub fn store(&self, interpreter: &str, site_packages_fingerprint: Option<i64>, packages: &[String]) -> Result<()> {
+        let packages = serde_json::to_string(packages).unwrap_or_else(|_| "[]".to_string());
+        let updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO inventories (interpreter, site_packages_fingerprint, packages, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(interpreter) DO UPDATE SET
+                site_packages_fingerprint = excluded.site_packages_fingerprint,
+                packages = excluded.packages,
+                updated_at = excluded.updated_at",
+            params![interpreter, site_packages_fingerprint, packages, updated_at],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn stored_inventories_load_back_as_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = InventoryCache::open(&dir.path().join("nested").join("inventory.db")).unwrap();
+        assert!(cache.load("/pyenv/versions/3.12.4/bin/python").unwrap().is_none());
+
+        cache.store("/pyenv/versions/3.12.4/bin/python", Some(42), &packages(&["pip 24.0"])).unwrap();
+        let inventory = cache.load("/pyenv/versions/3.12.4/bin/python").unwrap().unwrap();
+        assert_eq!(inventory.packages, ["pip 24.0"]);
+        assert_eq!(inventory.site_packages_fingerprint, Some(42));
+        assert_eq!(inventory.freshness, Freshness::Cached);
+        assert!(cache.load("system").unwrap().is_none());
+    }
+
+    #[test]
+    fn storing_again_replaces_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = InventoryCache::open(&dir.path().join("inventory.db")).unwrap();
+        cache.store("system", Some(1), &packages(&["pip 23.2.1"])).unwrap();
+        cache.store("system", None, &packages(&["pip 24.0", "requests 2.32.3"])).unwrap();
+
+        let inventory = cache.load("system").unwrap().unwrap();
+        assert_eq!(inventory.packages, ["pip 24.0", "requests 2.32.3"]);
+        assert_eq!(inventory.site_packages_fingerprint, None);
+    }
+
+    #[test]
+    fn entries_survive_reopening_and_bad_rows_read_as_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inventory.db");
+        InventoryCache::open(&path).unwrap().store("system", Some(7), &packages(&["six 1.16.0"])).unwrap();
+
+        let cache = InventoryCache::open(&path).unwrap();
+        assert_eq!(cache.load("system").unwrap().unwrap().packages, ["six 1.16.0"]);
+
+        // A row whose package list doesn't parse is re-read instead of trusted
+        cache.conn.lock().unwrap().execute("UPDATE inventories SET packages = 'not json'", []).unwrap();
+        assert!(cache.load("system").unwrap().is_none());
+    }
+
+    #[test]
+    fn a_cache_from_another_schema_starts_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inventory.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE inventories (
+                    interpreter TEXT PRIMARY KEY, site_packages_mtime INTEGER, packages TEXT NOT NULL, updated_at INTEGER NOT NULL
+                 );
+                 INSERT INTO inventories VALUES ('system', 1700000000, '[\"pip 23.2.1\"]', 0);",
+            )
+            .unwrap();
+
+        let cache = InventoryCache::open(&path).unwrap();
+        assert!(cache.load("system").unwrap().is_none());
+        cache.store("system", Some(3), &packages(&["pip 24.0"])).unwrap();
+        assert_eq!(InventoryCache::open(&path).unwrap().load("system").unwrap().unwrap().packages, ["pip 24.0"]);
+    }
+}
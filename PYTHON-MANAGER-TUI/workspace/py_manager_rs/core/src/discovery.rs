@@ -0,0 +1,141 @@
+use crate::error::{Error, Result};
+use crate::packages::clean_version;
+use crate::process::{self, CommandRunner};
+
+// Bare version names, without the `*` on the active one or annotations like
+// "(set by /home/me/.pyenv/version)"
+pub async fn get_python_versions(runner: &dyn CommandRunner) -> Result<Vec<String>> {
+    let versions = process::run(runner, "pyenv", &["versions"], &[]).await?;
+    Ok(versions
+        .lines()
+        .map(|line| clean_version(line.trim().trim_start_matches('*')))
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+// Everything `pyenv install` can build, newest first; the "Available versions:"
+// header some pyenv releases print is skipped
+pub async fn get_available_versions(runner: &dyn CommandRunner) -> Result<Vec<String>> {
+    let output = process::run(runner, "pyenv", &["install", "--list"], &[]).await?;
+    let mut versions: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.ends_with(':'))
+        .map(String::from)
+        .collect();
+    versions.reverse();
+    Ok(versions)
+}
+
+// The version pyenv actually resolves to here, so a local `.python-version` counts too
+pub async fn get_current_python_version(runner: &dyn CommandRunner) -> Result<String> {
+    let output = process::run(runner, "pyenv", &["version-name"], &[]).await?;
+    Ok(output.trim().to_string())
+}
+
+pub async fn get_python_env(runner: &dyn CommandRunner) -> Result<String> {
+    let path = match process::run(runner, "which", &["python3"], &[]).await {
+        Ok(path) => path.trim().to_string(),
+        // `which` exits non-zero when there is no python3 at all
+        Err(Error::CommandFailed { .. }) => return Ok("None".to_string()),
+        Err(e) => return Err(e),
+    };
+    if path.contains(".pyenv") {
+        Ok("Pyenv".to_string())
+    } else if path.contains("/usr/local/") {
+        Ok("Homebrew".to_string())
+    } else {
+        Ok("System".to_string())
+    }
+}
+
+// The environment pip runs in, e.g. a venv's directory; "Unknown" when the output
+// of `pip --version` doesn't say
+pub async fn get_virtual_env(runner: &dyn CommandRunner) -> Result<String> {
+    let version_info = process::run(runner, "pip", &["--version"], &[]).await?;
+    Ok(pip_environment(&version_info).unwrap_or_else(|| "Unknown".to_string()))
+}
+
+// The prefix pip is installed under, from "pip 24.0 from <prefix>/lib/python3.12/
+// site-packages/pip (python 3.12)". The path may contain spaces, so it is whatever
+// lies between " from " and the trailing "(python X.Y)".
+fn pip_environment(version_info: &str) -> Option<String> {
+    let (_, rest) = version_info.trim().split_once(" from ")?;
+    let path = rest.rsplit_once(" (python").map_or(rest, |(path, _)| path);
+    // lib/pythonX.Y/site-packages on Unix, Lib\site-packages on Windows
+    let prefix = path.find("/lib/python").or_else(|| path.find("\\Lib\\site-packages")).map_or(path, |i| &path[..i]);
+    (!prefix.is_empty()).then(|| prefix.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ScriptedRunner;
+
+    const PYENV_VERSIONS: &str = "  system\n  3.11.7\n* 3.12.4 (set by /home/me/.pyenv/version)\n\n";
+
+    #[tokio::test]
+    async fn python_versions_strip_the_active_marker() {
+        let runner = ScriptedRunner::new().ok("pyenv versions", PYENV_VERSIONS);
+        let versions = get_python_versions(&runner).await.unwrap();
+        assert_eq!(versions, vec!["system", "3.11.7", "3.12.4"]);
+    }
+
+    #[tokio::test]
+    async fn python_versions_without_pyenv_is_an_error() {
+        let runner = ScriptedRunner::new().missing("pyenv");
+        let err = get_python_versions(&runner).await.unwrap_err();
+        assert_eq!(err.to_string(), "`pyenv` was not found on PATH");
+    }
+
+    #[tokio::test]
+    async fn available_versions_list_newest_first_without_the_header() {
+        let runner = ScriptedRunner::new().ok("pyenv install --list", "Available versions:\n  3.11.7\n  Anaconda3-2024.02\n  3.12.4\n");
+        let versions = get_available_versions(&runner).await.unwrap();
+        assert_eq!(versions, vec!["3.12.4", "Anaconda3-2024.02", "3.11.7"]);
+    }
+
+    #[tokio::test]
+    async fn current_version_is_trimmed() {
+        let runner = ScriptedRunner::new().ok("pyenv version-name", "3.12.4\n");
+        assert_eq!(get_current_python_version(&runner).await.unwrap(), "3.12.4");
+    }
+
+    #[tokio::test]
+    async fn python_env_is_classified_by_path() {
+        let cases = [
+            ("/home/me/.pyenv/shims/python3\n", "Pyenv"),
+            ("/usr/local/bin/python3\n", "Homebrew"),
+            ("/usr/bin/python3\n", "System"),
+        ];
+        for (path, expected) in cases {
+            let runner = ScriptedRunner::new().ok("which python3", path);
+            assert_eq!(get_python_env(&runner).await.unwrap(), expected);
+        }
+
+        let runner = ScriptedRunner::new().fail("which python3", 1, "");
+        assert_eq!(get_python_env(&runner).await.unwrap(), "None");
+    }
+
+    #[tokio::test]
+    async fn virtual_env_is_where_pip_is_installed() {
+        let runner = ScriptedRunner::new().ok(
+            "pip --version",
+            "pip 24.0 from /home/me/.pyenv/versions/3.12.4/lib/python3.12/site-packages/pip (python 3.12)\n",
+        );
+        assert_eq!(get_virtual_env(&runner).await.unwrap(), "/home/me/.pyenv/versions/3.12.4");
+
+        let cases = [
+            (
+                "pip 24.0 from /home/me/my project/.venv/lib/python3.11/site-packages/pip (python 3.11)",
+                Some("/home/me/my project/.venv"),
+            ),
+            ("pip 22.0.2 from /usr/lib/python3/dist-packages/pip (python 3.10)", Some("/usr")),
+            ("pip 24.0 from C:\\Python312\\Lib\\site-packages\\pip (python 3.12)", Some("C:\\Python312")),
+            ("pip: command output changed", None),
+        ];
+        for (output, expected) in cases {
+            assert_eq!(pip_environment(output).as_deref(), expected, "{}", output);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use std::io;
+
+// Everything discovery, package listing, index searches and operations can fail with
+#[derive(Debug)]
+pub enum Error {
+    // The tool isn't installed or isn't on PATH
+    ToolNotFound { tool: &'static str },
+    // The tool exists but couldn't be started
+    Spawn { tool: &'static str, source: io::Error },
+    // The tool ran and exited unsuccessfully
+    CommandFailed { tool: &'static str, args: String, code: Option<i32>, stderr: String },
+    // The package index couldn't be reached or answered with an error
+    Index(String),
+    // The on-disk package cache couldn't be read or written
+    Cache(rusqlite::Error),
+    // The backend needed for this is turned off in the config
+    Disabled(&'static str),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn spawn(tool: &'static str, source: io::Error) -> Error {
+        if source.kind() == io::ErrorKind::NotFound {
+            Error::ToolNotFound { tool }
+        } else {
+            Error::Spawn { tool, source }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ToolNotFound { tool } => write!(f, "`{}` was not found on PATH", tool),
+            Error::Spawn { tool, source } => write!(f, "failed to run `{}`: {}", tool, source),
+            Error::CommandFailed { tool, args, code, stderr } => {
+                match code {
+                    Some(code) => write!(f, "`{} {}` exited with status {}", tool, args, code)?,
+                    None => write!(f, "`{} {}` was terminated", tool, args)?,
+                }
+                // The last line of stderr is usually the useful one
+                match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+                    Some(line) => write!(f, ": {}", line.trim()),
+                    None => Ok(()),
+                }
+            }
+            Error::Index(message) => write!(f, "package index error: {}", message),
+            Error::Cache(e) => write!(f, "package cache error: {}", e),
+            Error::Disabled(backend) => write!(f, "the {} backend is disabled in the config", backend),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Spawn { source, .. } => Some(source),
+            Error::Cache(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Error {
+        Error::Cache(e)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Error {
+        Error::Index(e.to_string())
+    }
+}
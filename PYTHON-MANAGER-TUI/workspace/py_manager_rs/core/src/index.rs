@@ -0,0 +1,34 @@
+use reqwest::Client;
+use scraper::{Html, Selector};
+use tracing::debug;
+
+use crate::error::{Error, Result};
+use crate::LOG_PACKAGE_LISTS;
+
+// `index_url` is the index's base URL, e.g. https://pypi.org
+pub async fn search_pypi(index_url: &str, query: &str) -> Result<Vec<String>> {
+    debug!(index_url, query, "searching PyPI");
+    let client = Client::new();
+    let url = format!("{}/search/?q={}", index_url.trim_end_matches('/'), query);
+    let response = client.get(&url).send().await?;
+
+    if !response.status().is_success() {
+        return Err(Error::Index(format!("PyPI answered with status {}", response.status())));
+    }
+
+    let text = response.text().await?;
+    let document = Html::parse_document(&text);
+    let selector = Selector::parse(".package-snippet")
+        .map_err(|e| Error::Index(format!("invalid result selector: {:?}", e)))?;
+
+    let packages: Vec<String> = document
+        .select(&selector)
+        .map(|element| element.value().attr("data-name").unwrap_or("").to_string())
+        .collect();
+
+    debug!(query, results = packages.len(), "PyPI search finished");
+    if LOG_PACKAGE_LISTS {
+        debug!(?packages, "PyPI search results");
+    }
+    Ok(packages)
+}
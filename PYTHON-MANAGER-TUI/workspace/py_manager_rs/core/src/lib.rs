@@ -0,0 +1,16 @@
+// What py_manager2 knows about Python installs without any terminal: finding and
+// switching versions through pyenv, reading installed packages from site-packages
+// or pip, caching those lists, and searching the package index. Commands go
+// through a `CommandRunner` so tests can script pyenv's replies.
+
+pub mod cache;
+pub mod discovery;
+pub mod error;
+pub mod index;
+pub mod operations;
+pub mod packages;
+pub mod process;
+pub mod site_packages;
+
+// Full package lists are only worth their size while developing
+pub const LOG_PACKAGE_LISTS: bool = cfg!(debug_assertions);
@@ -0,0 +1,67 @@
+use std::fmt;
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::error::Result;
+use crate::process::{self, CommandRunner};
+
+// Where `pyenv` records the chosen version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    // ~/.pyenv/version, for every shell
+    Global,
+    // .python-version in the current directory
+    Local,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scope::Global => "global",
+            Scope::Local => "local",
+        })
+    }
+}
+
+pub async fn install_package(runner: &dyn CommandRunner, package: &str) -> Result<()> {
+    info!(package, "installing package");
+    match process::run(runner, "pip", &["install", package], &[]).await {
+        Ok(_) => {
+            info!(package, "installed package");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(package, error = %e, "failed to install package");
+            Err(e)
+        }
+    }
+}
+
+pub async fn uninstall_package(runner: &dyn CommandRunner, package: &str) -> Result<()> {
+    info!(package, "uninstalling package");
+    match process::run(runner, "pip", &["uninstall", "--yes", package], &[]).await {
+        Ok(_) => {
+            info!(package, "uninstalled package");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(package, error = %e, "failed to uninstall package");
+            Err(e)
+        }
+    }
+}
+
+pub async fn switch_version(runner: &dyn CommandRunner, version: &str, scope: Scope) -> Result<()> {
+    info!(version, %scope, "switching Python version");
+    let scope = scope.to_string();
+    process::run(runner, "pyenv", &[scope.as_str(), version], &[]).await?;
+    Ok(())
+}
+
+// `python -m venv` with the interpreter of `version`
+pub async fn create_venv(runner: &dyn CommandRunner, version: &str, path: &Path) -> Result<()> {
+    info!(version, path = %path.display(), "creating virtual environment");
+    let path = path.to_string_lossy();
+    process::run(runner, "pyenv", &["exec", "python", "-m", "venv", path.as_ref()], &[("PYENV_VERSION", version)]).await?;
+    Ok(())
+}
@@ -0,0 +1,215 @@
+// Listing a version's installed packages, from its site-packages or through pip,
+// and comparing and caching the lists.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+use crate::cache::{Inventory, InventoryCache};
+use crate::error::{Error, Result};
+use crate::process::{self, CommandRunner};
+use crate::{site_packages, LOG_PACKAGE_LISTS};
+
+// Where a version's package list may be read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sources {
+    // Read installed distributions straight from site-packages
+    pub site_packages: bool,
+    // Ask `pip list` when site-packages can't be read
+    pub pip: bool,
+}
+
+impl Default for Sources {
+    fn default() -> Sources {
+        Sources { site_packages: true, pip: true }
+    }
+}
+
+pub async fn get_packages_for_version(runner: &dyn CommandRunner, sources: Sources, version: &str) -> Result<Vec<String>> {
+    debug!(version, "fetching packages");
+    let clean_version = clean_version(version);
+
+    // Read the interpreter's site-packages directly; only fall back to pip when
+    // there is nothing to probe (e.g. the system interpreter)
+    if let Some(prefix) = site_packages::version_prefix(&clean_version).filter(|_| sources.site_packages) {
+        let dirs = site_packages::site_packages_dirs(&prefix);
+        if !dirs.is_empty() || !sources.pip {
+            let distributions = tokio::task::spawn_blocking({
+                let dirs = dirs.clone();
+                move || site_packages::read_distributions(&dirs)
+            })
+            .await
+            .unwrap_or_default();
+            debug!(version, count = distributions.len(), ?dirs, "read packages from site-packages");
+            return Ok(format_package_columns(&distributions));
+        }
+    }
+
+    if !sources.pip {
+        return Err(Error::Disabled("pip"));
+    }
+    debug!(version, "no site-packages found, falling back to pip");
+    let packages = process::run(
+        runner,
+        "pyenv",
+        &["exec", "pip", "list", "--format=columns"],
+        &[("PYENV_VERSION", &clean_version)],
+    )
+    .await?;
+    let packages = parse_pip_columns(&packages);
+    debug!(version, count = packages.len(), "read packages from pip");
+    if LOG_PACKAGE_LISTS {
+        debug!(version, ?packages, "pip list");
+    }
+    Ok(packages)
+}
+
+// Drop the header and rule lines of `pip list --format=columns` output
+fn parse_pip_columns(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(2) // Skip the header lines
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// `pyenv versions` lines carry annotations like "(set by ...)"; keep just the name
+pub fn clean_version(version: &str) -> String {
+    version.split_whitespace().next().unwrap_or("").to_string()
+}
+
+// What differs between two package lists, by name: "- name version" only on the
+// left, "+ name version" only on the right, "~ name left -> right" for changed
+// versions
+pub fn diff_packages(left: &[String], right: &[String]) -> Vec<String> {
+    let split = |packages: &[String]| -> BTreeMap<String, (String, String)> {
+        packages
+            .iter()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let name = fields.next()?.to_string();
+                let version = fields.next().unwrap_or("").to_string();
+                Some((name.to_lowercase(), (name, version)))
+            })
+            .collect()
+    };
+    let (left, right) = (split(left), split(right));
+    let names: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    names
+        .into_iter()
+        .filter_map(|key| match (left.get(key), right.get(key)) {
+            (Some((name, version)), None) => Some(format!("- {} {}", name, version)),
+            (None, Some((name, version))) => Some(format!("+ {} {}", name, version)),
+            (Some((name, old)), Some((_, new))) if old != new => Some(format!("~ {} {} -> {}", name, old, new)),
+            _ => None,
+        })
+        .collect()
+}
+
+// Site-packages directories of every pyenv-managed version, for the file watcher
+pub fn site_packages_by_version(versions: &[String]) -> Vec<(String, PathBuf)> {
+    versions
+        .iter()
+        .map(|version| clean_version(version))
+        .filter_map(|version| Some((site_packages::version_prefix(&version)?, version)))
+        .flat_map(|(prefix, version)| {
+            site_packages::site_packages_dirs(&prefix)
+                .into_iter()
+                .map(move |dir| (version.clone(), dir))
+        })
+        .collect()
+}
+
+// Lay distributions out like `pip list --format=columns` so both sources look the same
+fn format_package_columns(distributions: &[site_packages::Distribution]) -> Vec<String> {
+    let width = distributions.iter().map(|d| d.name.len()).max().unwrap_or(0).max("Package".len());
+    distributions
+        .iter()
+        .map(|d| format!("{:<width$} {}", d.name, d.version, width = width))
+        .collect()
+}
+
+// Cache key for a version: its interpreter path when pyenv manages it, the name otherwise
+pub fn interpreter_key(clean_version: &str) -> String {
+    match site_packages::version_prefix(clean_version) {
+        Some(prefix) => prefix.join("bin").join("python").display().to_string(),
+        None => clean_version.to_string(),
+    }
+}
+
+// Read every stored inventory up front so the first frame already has package lists
+pub fn load_cached_inventories(versions: &[String], store: &InventoryCache) -> HashMap<String, Inventory> {
+    let mut cache = HashMap::new();
+    for version in versions {
+        let version = clean_version(version);
+        match store.load(&interpreter_key(&version)) {
+            Ok(Some(inventory)) => {
+                cache.insert(version, inventory);
+            }
+            Ok(None) => {}
+            Err(e) => warn!(version = %version, error = %e, "failed to read cached packages"),
+        }
+    }
+    cache
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ScriptedRunner;
+
+    const PIP_LIST: &str = "Package    Version\n---------- -------\npip        24.0\nrequests   2.32.3\n";
+
+    #[test]
+    fn clean_version_drops_annotations() {
+        assert_eq!(clean_version("3.12.4 (set by /home/me/.pyenv/version)"), "3.12.4");
+        assert_eq!(clean_version("system"), "system");
+        assert_eq!(clean_version(""), "");
+    }
+
+    #[test]
+    fn diff_lists_removed_added_and_changed_packages() {
+        let left = vec!["pip      24.0".to_string(), "requests 2.31.0".to_string(), "six      1.16.0".to_string()];
+        let right = vec!["pip        24.0".to_string(), "Requests   2.32.3".to_string(), "urllib3    2.2.2".to_string()];
+        assert_eq!(
+            diff_packages(&left, &right),
+            vec!["~ requests 2.31.0 -> 2.32.3", "- six 1.16.0", "+ urllib3 2.2.2"]
+        );
+    }
+
+    #[test]
+    fn package_columns_are_aligned_on_the_longest_name() {
+        let distributions = vec![
+            site_packages::Distribution { name: "pip".to_string(), version: "24.0".to_string() },
+            site_packages::Distribution { name: "requests".to_string(), version: "2.32.3".to_string() },
+        ];
+        assert_eq!(format_package_columns(&distributions), vec!["pip      24.0", "requests 2.32.3"]);
+    }
+
+    // "system" is never read natively, so this always exercises the pip fallback
+    #[tokio::test]
+    async fn system_packages_come_from_pip_list() {
+        let runner = ScriptedRunner::new().ok("pyenv exec pip list --format=columns", PIP_LIST);
+        let packages = get_packages_for_version(&runner, Sources::default(), "system").await.unwrap();
+        assert_eq!(packages, vec!["pip        24.0", "requests   2.32.3"]);
+
+        let call = &runner.calls()[0];
+        assert_eq!(call.envs, vec![("PYENV_VERSION".to_string(), "system".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn disabled_pip_is_not_run() {
+        let runner = ScriptedRunner::new();
+        let sources = Sources { pip: false, ..Sources::default() };
+        let err = get_packages_for_version(&runner, sources, "system").await.unwrap_err();
+        assert!(matches!(err, Error::Disabled("pip")));
+        assert!(runner.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failing_pip_list_is_an_error() {
+        let runner = ScriptedRunner::new().fail("pyenv exec pip list --format=columns", 1, "No module named pip\n");
+        let err = get_packages_for_version(&runner, Sources::default(), "system").await.unwrap_err();
+        assert!(err.to_string().ends_with("No module named pip"), "{}", err);
+    }
+}
@@ -0,0 +1,144 @@
+// Every external tool (pyenv, pip, which) is run through a `CommandRunner`, so the
+// code that parses their output can be driven by a scripted fake in tests. The fake
+// is only built for this crate's tests and with the `testing` feature.
+
+use futures::future::BoxFuture;
+use std::io;
+use std::time::Instant;
+use tokio::process::Command;
+use tracing::{debug, debug_span, warn, Instrument};
+
+use crate::error::{Error, Result};
+
+#[cfg(any(test, feature = "testing"))]
+mod scripted;
+#[cfg(any(test, feature = "testing"))]
+pub use scripted::{Call, ScriptedRunner};
+
+// What a finished tool run produced
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+    // `None` when the process was killed by a signal
+    pub code: Option<i32>,
+}
+
+pub trait CommandRunner: Send + Sync {
+    fn output<'a>(
+        &'a self,
+        tool: &'static str,
+        args: &'a [&'a str],
+        envs: &'a [(&'a str, &'a str)],
+    ) -> BoxFuture<'a, io::Result<Output>>;
+}
+
+// Spawns the real processes
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn output<'a>(
+        &'a self,
+        tool: &'static str,
+        args: &'a [&'a str],
+        envs: &'a [(&'a str, &'a str)],
+    ) -> BoxFuture<'a, io::Result<Output>> {
+        Box::pin(async move {
+            let output = Command::new(tool).args(args).envs(envs.iter().copied()).output().await?;
+            Ok(Output {
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                code: output.status.code(),
+            })
+        })
+    }
+}
+
+// Run an external tool to completion and hand back its stdout, turning a missing
+// tool or a non-zero exit into an `Error`
+pub async fn run(runner: &dyn CommandRunner, tool: &'static str, args: &[&str], envs: &[(&str, &str)]) -> Result<String> {
+    let command = std::iter::once(tool).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+    let span = debug_span!("command", command = %command);
+    let started = Instant::now();
+    let output = runner.output(tool, args, envs).instrument(span.clone()).await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            span.in_scope(|| warn!(duration_ms, error = %e, "command could not be started"));
+            return Err(Error::spawn(tool, e));
+        }
+    };
+    span.in_scope(|| match output.code {
+        Some(0) => debug!(duration_ms, exit_code = 0, "command finished"),
+        code => warn!(duration_ms, exit_code = ?code, "command failed"),
+    });
+
+    if output.code == Some(0) {
+        Ok(output.stdout)
+    } else {
+        Err(Error::CommandFailed {
+            tool,
+            args: args.join(" "),
+            code: output.code,
+            stderr: output.stderr,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn run_returns_stdout_on_success() {
+        let runner = ScriptedRunner::new().ok("pyenv version-name", "3.12.4\n");
+        let stdout = run(&runner, "pyenv", &["version-name"], &[]).await.unwrap();
+        assert_eq!(stdout, "3.12.4\n");
+        assert_eq!(runner.command_lines(), vec!["pyenv version-name"]);
+    }
+
+    #[tokio::test]
+    async fn run_turns_non_zero_exit_into_command_failed() {
+        let runner = ScriptedRunner::new().fail("pyenv global 9.9", 1, "pyenv: version `9.9' not installed\n");
+        let err = run(&runner, "pyenv", &["global", "9.9"], &[]).await.unwrap_err();
+        match &err {
+            Error::CommandFailed { tool, args, code, .. } => {
+                assert_eq!(*tool, "pyenv");
+                assert_eq!(args, "global 9.9");
+                assert_eq!(*code, Some(1));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.to_string().ends_with("pyenv: version `9.9' not installed"));
+    }
+
+    #[tokio::test]
+    async fn missing_tool_is_reported_as_not_found() {
+        let runner = ScriptedRunner::new().missing("pyenv");
+        let err = run(&runner, "pyenv", &["versions"], &[]).await.unwrap_err();
+        assert!(matches!(err, Error::ToolNotFound { tool: "pyenv" }));
+    }
+
+    #[tokio::test]
+    async fn repeated_scripts_answer_in_order_then_repeat() {
+        let runner = ScriptedRunner::new()
+            .ok("pyenv version-name", "3.11.7\n")
+            .ok("pyenv version-name", "3.12.4\n");
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            seen.push(run(&runner, "pyenv", &["version-name"], &[]).await.unwrap());
+        }
+        assert_eq!(seen, vec!["3.11.7\n", "3.12.4\n", "3.12.4\n"]);
+    }
+
+    #[tokio::test]
+    async fn calls_record_environment() {
+        let runner = ScriptedRunner::new().ok("pyenv exec pip list --format=columns", "");
+        run(&runner, "pyenv", &["exec", "pip", "list", "--format=columns"], &[("PYENV_VERSION", "3.12.4")])
+            .await
+            .unwrap();
+        assert_eq!(runner.calls()[0].envs, vec![("PYENV_VERSION".to_string(), "3.12.4".to_string())]);
+    }
+}
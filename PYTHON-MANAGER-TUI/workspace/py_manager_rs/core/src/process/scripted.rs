@@ -0,0 +1,143 @@
+// A `CommandRunner` that answers from a script, for tests that need pyenv and pip
+// to say something particular without running them.
+
+use futures::future::{self, BoxFuture};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Mutex;
+
+use super::{CommandRunner, Output};
+
+// One invocation seen by a `ScriptedRunner`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub tool: String,
+    pub args: Vec<String>,
+    pub envs: Vec<(String, String)>,
+}
+
+impl Call {
+    // The invocation as it would be typed, e.g. "pyenv global 3.12.4"
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.tool.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+struct Script {
+    // For a missing tool, just the tool name
+    command_line: String,
+    responses: VecDeque<Output>,
+    missing: bool,
+}
+
+// Answers with recorded output instead of spawning anything. Responses are matched
+// on the full command line; a command scripted more than once answers in order and
+// repeats its last response. Anything unscripted fails as if the tool were missing.
+#[derive(Default)]
+pub struct ScriptedRunner {
+    scripts: Mutex<Vec<Script>>,
+    calls: Mutex<Vec<Call>>,
+}
+
+impl ScriptedRunner {
+    pub fn new() -> ScriptedRunner {
+        ScriptedRunner::default()
+    }
+
+    // `command_line` succeeds and prints `stdout`
+    pub fn ok(self, command_line: &str, stdout: &str) -> ScriptedRunner {
+        self.respond(
+            command_line,
+            Output {
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+                code: Some(0),
+            },
+        )
+    }
+
+    // `command_line` exits with `code` and prints `stderr`
+    pub fn fail(self, command_line: &str, code: i32, stderr: &str) -> ScriptedRunner {
+        self.respond(
+            command_line,
+            Output {
+                stdout: String::new(),
+                stderr: stderr.to_string(),
+                code: Some(code),
+            },
+        )
+    }
+
+    // `tool` is not on PATH, whatever it is called with
+    pub fn missing(self, tool: &str) -> ScriptedRunner {
+        self.scripts.lock().unwrap().push(Script {
+            command_line: tool.to_string(),
+            responses: VecDeque::new(),
+            missing: true,
+        });
+        self
+    }
+
+    pub fn respond(self, command_line: &str, output: Output) -> ScriptedRunner {
+        {
+            let mut scripts = self.scripts.lock().unwrap();
+            match scripts.iter_mut().find(|s| !s.missing && s.command_line == command_line) {
+                Some(script) => script.responses.push_back(output),
+                None => scripts.push(Script {
+                    command_line: command_line.to_string(),
+                    responses: VecDeque::from(vec![output]),
+                    missing: false,
+                }),
+            }
+        }
+        self
+    }
+
+    // Every invocation so far, oldest first
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn command_lines(&self) -> Vec<String> {
+        self.calls().iter().map(Call::command_line).collect()
+    }
+
+    fn answer(&self, call: &Call) -> io::Result<Output> {
+        let command_line = call.command_line();
+        let mut scripts = self.scripts.lock().unwrap();
+        if scripts.iter().any(|s| s.missing && s.command_line == call.tool) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "not installed"));
+        }
+        let script = scripts
+            .iter_mut()
+            .find(|s| s.command_line == command_line)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("unscripted command: {}", command_line)))?;
+        let output = if script.responses.len() > 1 {
+            script.responses.pop_front()
+        } else {
+            script.responses.front().cloned()
+        };
+        Ok(output.unwrap_or_default())
+    }
+}
+
+impl CommandRunner for ScriptedRunner {
+    fn output<'a>(
+        &'a self,
+        tool: &'static str,
+        args: &'a [&'a str],
+        envs: &'a [(&'a str, &'a str)],
+    ) -> BoxFuture<'a, io::Result<Output>> {
+        let call = Call {
+            tool: tool.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            envs: envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        };
+        let result = self.answer(&call);
+        self.calls.lock().unwrap().push(call);
+        Box::pin(future::ready(result))
+    }
+}
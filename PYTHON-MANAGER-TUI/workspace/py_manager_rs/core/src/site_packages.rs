@@ -0,0 +1,364 @@
+// Reads installed distributions straight from an interpreter's site-packages
+// directories, so listing packages doesn't have to spawn `pip list`.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distribution {
+    pub name: String,
+    pub version: String,
+}
+
+// Root of the pyenv installation, honouring `PYENV_ROOT` like pyenv itself does
+pub fn pyenv_root() -> Option<PathBuf> {
+    env::var_os("PYENV_ROOT")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".pyenv")))
+}
+
+// Install prefix of a pyenv version; `None` for "system" or anything pyenv doesn't manage
+pub fn version_prefix(version: &str) -> Option<PathBuf> {
+    if version.is_empty() || version == "system" {
+        return None;
+    }
+    let prefix = pyenv_root()?.join("versions").join(version);
+    if prefix.is_dir() {
+        Some(prefix)
+    } else {
+        None
+    }
+}
+
+// Where a version lives on disk, shown on its page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interpreter {
+    // None when pyenv doesn't manage the version, e.g. "system"
+    pub prefix: Option<PathBuf>,
+    // The python binary below the prefix, if it is still there
+    pub executable: Option<PathBuf>,
+    pub site_packages: Vec<PathBuf>,
+}
+
+pub fn inspect(version: &str) -> Interpreter {
+    match version_prefix(version) {
+        Some(prefix) => inspect_prefix(prefix),
+        None => Interpreter::default(),
+    }
+}
+
+pub fn inspect_prefix(prefix: PathBuf) -> Interpreter {
+    let executable = ["python3", "python", "python.exe"]
+        .iter()
+        .map(|name| if name.ends_with(".exe") { prefix.join(name) } else { prefix.join("bin").join(name) })
+        .find(|path| path.is_file());
+    let site_packages = site_packages_dirs(&prefix);
+    Interpreter { prefix: Some(prefix), executable, site_packages }
+}
+
+// Probe the usual site-packages locations below an interpreter prefix
+pub fn site_packages_dirs(prefix: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    // POSIX layout: <prefix>/lib/pythonX.Y/site-packages
+    if let Ok(entries) = fs::read_dir(prefix.join("lib")) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with("python") {
+                let site = entry.path().join("site-packages");
+                if site.is_dir() {
+                    dirs.push(site);
+                }
+            }
+        }
+    }
+
+    // Windows layout: <prefix>\Lib\site-packages
+    let windows_site = prefix.join("Lib").join("site-packages");
+    if windows_site.is_dir() && !dirs.contains(&windows_site) {
+        dirs.push(windows_site);
+    }
+
+    dirs.sort();
+    dirs
+}
+
+// Collect every `*.dist-info` and `*.egg-info` entry in the given directories,
+// sorted case-insensitively by name the same way `pip list` orders them
+pub fn read_distributions(dirs: &[PathBuf]) -> Vec<Distribution> {
+    let mut distributions: Vec<Distribution> = Vec::new();
+
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+
+            let (stem, metadata_path) = if let Some(stem) = file_name.strip_suffix(".dist-info") {
+                (stem, path.join("METADATA"))
+            } else if let Some(stem) = file_name.strip_suffix(".egg-info") {
+                // An egg-info can be a directory holding PKG-INFO or the PKG-INFO file itself
+                if path.is_dir() {
+                    (stem, path.join("PKG-INFO"))
+                } else {
+                    (stem, path.clone())
+                }
+            } else {
+                continue;
+            };
+
+            let distribution = fs::read_to_string(&metadata_path)
+                .ok()
+                .and_then(|text| parse_metadata(&text))
+                .or_else(|| parse_dir_name(stem));
+
+            if let Some(distribution) = distribution {
+                let key = normalize_name(&distribution.name);
+                if !distributions.iter().any(|d| normalize_name(&d.name) == key) {
+                    distributions.push(distribution);
+                }
+            }
+        }
+    }
+
+    distributions.sort_by_key(|d| d.name.to_lowercase());
+    distributions
+}
+
+// Pull `Name` and `Version` out of the RFC 822 style header block of METADATA/PKG-INFO
+pub fn parse_metadata(text: &str) -> Option<Distribution> {
+    let mut name = None;
+    let mut version = None;
+
+    for line in text.lines() {
+        // Headers end at the first blank line; the rest is the long description
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            match key.trim().to_ascii_lowercase().as_str() {
+                "name" => name = Some(value.trim().to_string()),
+                "version" => version = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    Some(Distribution {
+        name: name.filter(|n| !n.is_empty())?,
+        version: version.unwrap_or_default(),
+    })
+}
+
+// Fall back to the `<name>-<version>[-pyX.Y]` naming of the metadata directory itself
+fn parse_dir_name(stem: &str) -> Option<Distribution> {
+    let mut parts = stem.splitn(3, '-');
+    let name = parts.next().filter(|n| !n.is_empty())?;
+    let version = parts.next().unwrap_or("");
+    Some(Distribution {
+        name: name.to_string(),
+        version: version.to_string(),
+    })
+}
+
+// PEP 503 normalisation so `Foo_Bar` and `foo-bar` count as the same distribution
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+// Identifies the state of the directories: their modification times in nanoseconds
+// plus the names of every entry. Installing, upgrading or removing a distribution
+// adds or renames a `*.dist-info` directory, so the listing changes even when the
+// filesystem only keeps whole-second mtimes and two installs share a second.
+//
+// The result is stored in the inventory cache, so it is hashed with FNV-1a over
+// explicit bytes rather than std's hasher, whose output may change between releases.
+pub fn fingerprint(dirs: &[PathBuf]) -> Option<i64> {
+    let mut hash = Fnv1a::default();
+    let mut any = false;
+    for dir in dirs {
+        let modified = match fs::metadata(dir).and_then(|meta| meta.modified()) {
+            Ok(modified) => modified,
+            Err(_) => continue,
+        };
+        any = true;
+        hash.field(dir.as_os_str().as_encoded_bytes());
+        hash.field(&modified.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0).to_le_bytes());
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|entry| Some(entry.ok()?.file_name())).collect())
+            .unwrap_or_default();
+        names.sort();
+        for name in &names {
+            hash.field(name.as_encoded_bytes());
+        }
+    }
+    any.then_some(hash.0 as i64)
+}
+
+// 64-bit FNV-1a, the same on every platform and toolchain
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    // Bytes followed by their length, so "ab" + "c" and "a" + "bc" differ
+    fn field(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+        self.write(&(bytes.len() as u64).to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn metadata_headers_stop_at_the_first_blank_line() {
+        let text = "Metadata-Version: 2.1\nName: requests\nVersion: 2.32.3\n\nName: not-this\n";
+        assert_eq!(
+            parse_metadata(text),
+            Some(Distribution { name: "requests".to_string(), version: "2.32.3".to_string() })
+        );
+        assert_eq!(parse_metadata("Version: 1.0\n"), None);
+    }
+
+    #[test]
+    fn metadata_keys_are_case_insensitive_and_trimmed() {
+        let text = "name:  typing_extensions \nVERSION: 4.12.2\r\nSummary: Backported types\n";
+        assert_eq!(
+            parse_metadata(text),
+            Some(Distribution { name: "typing_extensions".to_string(), version: "4.12.2".to_string() })
+        );
+        // A name without a version still lists, with the version left blank
+        assert_eq!(parse_metadata("Name: local-thing\n").map(|d| d.version), Some(String::new()));
+    }
+
+    #[test]
+    fn directory_names_give_name_and_version() {
+        let parsed = |stem| parse_dir_name(stem).map(|d| (d.name, d.version));
+        assert_eq!(parsed("zope_interface-6.1"), Some(("zope_interface".to_string(), "6.1".to_string())));
+        // The `-pyX.Y` tag of an egg-info is not part of the version
+        assert_eq!(parsed("six-1.16.0-py3.12"), Some(("six".to_string(), "1.16.0".to_string())));
+        assert_eq!(parsed("bare"), Some(("bare".to_string(), String::new())));
+        assert_eq!(parsed(""), None);
+    }
+
+    #[test]
+    fn unreadable_metadata_falls_back_to_the_directory_name() {
+        let site = tempfile::tempdir().unwrap();
+        // An egg-info directory without its PKG-INFO, and METADATA with no Name header
+        fs::create_dir_all(site.path().join("attrs-23.2.0.egg-info")).unwrap();
+        write(&site.path().join("idna-3.7.dist-info").join("METADATA"), "Metadata-Version: 2.1\nVersion: 3.7\n");
+        write(&site.path().join("notes.txt"), "Name: not-a-distribution\n");
+
+        let names: Vec<(String, String)> = read_distributions(&[site.path().to_path_buf()])
+            .into_iter()
+            .map(|d| (d.name, d.version))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("attrs".to_string(), "23.2.0".to_string()), ("idna".to_string(), "3.7".to_string())]
+        );
+    }
+
+    #[test]
+    fn windows_prefixes_use_lib_site_packages() {
+        let prefix = tempfile::tempdir().unwrap();
+        let site = prefix.path().join("Lib").join("site-packages");
+        fs::create_dir_all(&site).unwrap();
+        assert_eq!(site_packages_dirs(prefix.path()), vec![site]);
+    }
+
+    #[test]
+    fn distributions_are_read_sorted_and_deduplicated() {
+        let prefix = tempfile::tempdir().unwrap();
+        let site = prefix.path().join("lib").join("python3.12").join("site-packages");
+        write(&site.join("requests-2.32.3.dist-info").join("METADATA"), "Name: requests\nVersion: 2.32.3\n");
+        write(&site.join("Babel-2.14.0.dist-info").join("METADATA"), "Name: Babel\nVersion: 2.14.0\n");
+        // No METADATA: fall back to the directory name
+        fs::create_dir_all(site.join("zope_interface-6.1.dist-info")).unwrap();
+        // Single-file egg-info, and a duplicate spelling of an existing name
+        write(&site.join("six-1.16.0-py3.12.egg-info"), "Name: six\nVersion: 1.16.0\n");
+        write(&site.join("Requests-2.0.egg-info").join("PKG-INFO"), "Name: Requests\nVersion: 2.0\n");
+        fs::create_dir_all(site.join("requests")).unwrap();
+
+        let dirs = site_packages_dirs(prefix.path());
+        assert_eq!(dirs, vec![site.clone()]);
+        let names: Vec<(String, String)> = read_distributions(&dirs)
+            .into_iter()
+            .map(|d| (d.name, d.version))
+            .collect();
+        assert_eq!(names.len(), 4);
+        assert_eq!(names[0], ("Babel".to_string(), "2.14.0".to_string()));
+        assert_eq!(names[1].0.to_lowercase(), "requests");
+        assert_eq!(names[2], ("six".to_string(), "1.16.0".to_string()));
+        assert_eq!(names[3], ("zope_interface".to_string(), "6.1".to_string()));
+    }
+
+    #[test]
+    fn inspecting_a_prefix_finds_its_python_and_site_packages() {
+        let prefix = tempfile::tempdir().unwrap();
+        let interpreter = inspect_prefix(prefix.path().to_path_buf());
+        assert_eq!(interpreter.executable, None);
+        assert!(interpreter.site_packages.is_empty());
+
+        write(&prefix.path().join("bin").join("python3"), "");
+        fs::create_dir_all(prefix.path().join("lib").join("python3.12").join("site-packages")).unwrap();
+        let interpreter = inspect_prefix(prefix.path().to_path_buf());
+        assert_eq!(interpreter.executable, Some(prefix.path().join("bin").join("python3")));
+        assert_eq!(interpreter.site_packages.len(), 1);
+    }
+
+    #[test]
+    fn missing_site_packages_reads_as_empty() {
+        let prefix = tempfile::tempdir().unwrap();
+        assert!(site_packages_dirs(prefix.path()).is_empty());
+        assert!(read_distributions(&[prefix.path().join("nope")]).is_empty());
+        assert_eq!(fingerprint(&[prefix.path().join("nope")]), None);
+    }
+
+    #[test]
+    fn fnv1a_matches_the_published_test_vectors() {
+        let hash = |text: &str| {
+            let mut hash = Fnv1a::default();
+            hash.write(text.as_bytes());
+            hash.0
+        };
+        assert_eq!(hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn a_new_dist_info_changes_the_fingerprint() {
+        let site = tempfile::tempdir().unwrap();
+        let dirs = [site.path().to_path_buf()];
+        write(&site.path().join("pip-24.0.dist-info").join("METADATA"), "Name: pip\nVersion: 24.0\n");
+        let before = fingerprint(&dirs).unwrap();
+        assert_eq!(fingerprint(&dirs), Some(before));
+
+        // Even if the directory's mtime didn't move, the listing did
+        fs::rename(site.path().join("pip-24.0.dist-info"), site.path().join("pip-24.1.dist-info")).unwrap();
+        assert_ne!(fingerprint(&dirs), Some(before));
+    }
+}
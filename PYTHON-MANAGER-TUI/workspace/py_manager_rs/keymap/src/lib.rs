@@ -0,0 +1,545 @@
+// What each key does in py_manager2. The app matches on `Action`s instead of raw
+// key codes; the defaults are vim keys plus arrow-key alternatives and can be
+// changed in the `[keys]` table of $XDG_CONFIG_HOME/py_manager/config.toml, which
+// py_manager2's config reads and hands to `Keymap::from_table`:
+//
+//     [keys]
+//     down = ["t", "down"]
+//     quit = ["ctrl-q"]
+//
+// Listing an action replaces all of its default keys.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Cancel,
+    SwitchVersion,
+    AddPackages,
+    Search,
+    EditSearch,
+    ToggleLog,
+    CycleLogFilter,
+    ToggleAvailable,
+    Suspend,
+    Help,
+    Command,
+    Find,
+    NextMatch,
+    PreviousMatch,
+    ToggleFilter,
+    PageUp,
+    PageDown,
+    MoveTabLeft,
+    MoveTabRight,
+    PinTab,
+    GrowPane,
+    ShrinkPane,
+    ZoomPane,
+    ToggleSplit,
+    NextPane,
+    Top,
+    Bottom,
+    HalfPageDown,
+    HalfPageUp,
+    ScreenTop,
+    ScreenMiddle,
+    ScreenBottom,
+    SetMark,
+    JumpToMark,
+}
+
+impl Action {
+    pub const ALL: [Action; 40] = [
+        Action::Quit,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Confirm,
+        Action::Cancel,
+        Action::SwitchVersion,
+        Action::AddPackages,
+        Action::Search,
+        Action::EditSearch,
+        Action::ToggleLog,
+        Action::CycleLogFilter,
+        Action::ToggleAvailable,
+        Action::Suspend,
+        Action::Help,
+        Action::Command,
+        Action::Find,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::ToggleFilter,
+        Action::PageUp,
+        Action::PageDown,
+        Action::MoveTabLeft,
+        Action::MoveTabRight,
+        Action::PinTab,
+        Action::GrowPane,
+        Action::ShrinkPane,
+        Action::ZoomPane,
+        Action::ToggleSplit,
+        Action::NextPane,
+        Action::Top,
+        Action::Bottom,
+        Action::HalfPageDown,
+        Action::HalfPageUp,
+        Action::ScreenTop,
+        Action::ScreenMiddle,
+        Action::ScreenBottom,
+        Action::SetMark,
+        Action::JumpToMark,
+    ];
+
+    // The name used in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::SwitchVersion => "switch-version",
+            Action::AddPackages => "add-packages",
+            Action::Search => "search",
+            Action::EditSearch => "edit-search",
+            Action::ToggleLog => "toggle-log",
+            Action::CycleLogFilter => "cycle-log-filter",
+            Action::ToggleAvailable => "toggle-available",
+            Action::Suspend => "suspend",
+            Action::Help => "help",
+            Action::Command => "command",
+            Action::Find => "find",
+            Action::NextMatch => "next-match",
+            Action::PreviousMatch => "previous-match",
+            Action::ToggleFilter => "toggle-filter",
+            Action::PageUp => "page-up",
+            Action::PageDown => "page-down",
+            Action::MoveTabLeft => "move-tab-left",
+            Action::MoveTabRight => "move-tab-right",
+            Action::PinTab => "pin-tab",
+            Action::GrowPane => "grow-pane",
+            Action::ShrinkPane => "shrink-pane",
+            Action::ZoomPane => "zoom-pane",
+            Action::ToggleSplit => "toggle-split",
+            Action::NextPane => "next-pane",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::HalfPageDown => "half-page-down",
+            Action::HalfPageUp => "half-page-up",
+            Action::ScreenTop => "screen-top",
+            Action::ScreenMiddle => "screen-middle",
+            Action::ScreenBottom => "screen-bottom",
+            Action::SetMark => "set-mark",
+            Action::JumpToMark => "jump-to-mark",
+        }
+    }
+
+    // What the action does in general; the app may describe it more precisely
+    // for the pane it applies to
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Previous tab",
+            Action::Right => "Next tab",
+            Action::Confirm => "Open or confirm the selection",
+            Action::Cancel => "Close or cancel",
+            Action::SwitchVersion => "Switch to the selected version",
+            Action::AddPackages => "Add packages to the selected version",
+            Action::Search => "List packages from the index",
+            Action::EditSearch => "Type a search query",
+            Action::ToggleLog => "Show or hide the log",
+            Action::CycleLogFilter => "Change the log level filter",
+            Action::ToggleAvailable => "Show or hide the versions pyenv can install",
+            Action::Suspend => "Suspend to the shell",
+            Action::Help => "Show or hide this help",
+            Action::Command => "Type a command",
+            Action::Find => "Search the list",
+            Action::NextMatch => "Next match",
+            Action::PreviousMatch => "Previous match",
+            Action::ToggleFilter => "Hide or show rows that don't match",
+            Action::PageUp => "Move up a page",
+            Action::PageDown => "Move down a page",
+            Action::MoveTabLeft => "Move the tab left",
+            Action::MoveTabRight => "Move the tab right",
+            Action::PinTab => "Pin or unpin the tab",
+            Action::GrowPane => "Grow the focused pane",
+            Action::ShrinkPane => "Shrink the focused pane",
+            Action::ZoomPane => "Zoom the focused pane to the whole screen",
+            Action::ToggleSplit => "Switch between side-by-side and stacked panes",
+            Action::NextPane => "Move between the versions and their packages",
+            Action::Top => "Go to the first row, or to row N after a count",
+            Action::Bottom => "Go to the last row, or to row N after a count",
+            Action::HalfPageDown => "Move down half a page",
+            Action::HalfPageUp => "Move up half a page",
+            Action::ScreenTop => "Go to the top of the screen",
+            Action::ScreenMiddle => "Go to the middle of the screen",
+            Action::ScreenBottom => "Go to the bottom of the screen",
+            Action::SetMark => "Mark the row with a letter",
+            Action::JumpToMark => "Go back to a marked row",
+        }
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q", "ctrl-c"],
+            Action::Up => &["k", "up"],
+            Action::Down => &["j", "down"],
+            Action::Left => &["h", "left"],
+            Action::Right => &["l", "right"],
+            Action::Confirm => &["enter"],
+            Action::Cancel => &["esc"],
+            Action::SwitchVersion => &["1"],
+            Action::AddPackages => &["2"],
+            Action::Search => &["s"],
+            Action::EditSearch => &["a"],
+            Action::ToggleLog => &["ctrl-l"],
+            Action::CycleLogFilter => &["f"],
+            Action::ToggleAvailable => &["i"],
+            Action::Suspend => &["ctrl-z"],
+            Action::Help => &["?"],
+            Action::Command => &[":"],
+            Action::Find => &["/"],
+            Action::NextMatch => &["n"],
+            Action::PreviousMatch => &["N"],
+            Action::ToggleFilter => &["F"],
+            Action::PageUp => &["pageup"],
+            Action::PageDown => &["pagedown"],
+            Action::MoveTabLeft => &["<"],
+            Action::MoveTabRight => &[">"],
+            Action::PinTab => &["p"],
+            Action::GrowPane => &["+", "="],
+            Action::ShrinkPane => &["-"],
+            Action::ZoomPane => &["z"],
+            Action::ToggleSplit => &["|"],
+            Action::NextPane => &["tab"],
+            // A letter is pressed twice, as in vim's `gg`
+            Action::Top => &["g", "home"],
+            Action::Bottom => &["G", "end"],
+            Action::HalfPageDown => &["ctrl-d"],
+            Action::HalfPageUp => &["ctrl-u"],
+            Action::ScreenTop => &["H"],
+            Action::ScreenMiddle => &["M"],
+            Action::ScreenBottom => &["L"],
+            Action::SetMark => &["m"],
+            Action::JumpToMark => &["'"],
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = KeymapError;
+
+    fn from_str(name: &str) -> Result<Action, KeymapError> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|action| action.name() == name)
+            .ok_or_else(|| KeymapError::UnknownAction(name.to_string()))
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// A key with its modifiers, normalized so that "L" and shift+l are the same key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        match code {
+            // The case of the character already says whether shift was held
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                Key { code: KeyCode::Char(c.to_ascii_uppercase()), modifiers: modifiers - KeyModifiers::SHIFT }
+            }
+            _ => Key { code, modifiers },
+        }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(key: KeyEvent) -> Key {
+        Key::new(key.code, key.modifiers)
+    }
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 15] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("space", KeyCode::Char(' ')),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl FromStr for Key {
+    type Err = KeymapError;
+
+    // "q", "L", "ctrl-z", "alt-enter", "shift-tab", "f5", "pagedown", ...
+    fn from_str(text: &str) -> Result<Key, KeymapError> {
+        let invalid = || KeymapError::InvalidKey(text.to_string());
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while let Some((modifier, tail)) = rest.split_once('-').filter(|(_, tail)| !tail.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+            rest = tail;
+        }
+
+        let lower = rest.to_ascii_lowercase();
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match NAMED_KEYS.iter().find(|(name, _)| *name == lower) {
+                Some((_, code)) => *code,
+                None => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(invalid()),
+                },
+            },
+        };
+        // Ctrl combinations arrive lowercase whatever the case was written as
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        Ok(Key::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            code => match NAMED_KEYS.iter().find(|(_, named)| *named == code) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum KeymapError {
+    // `[keys]` names an action that doesn't exist
+    UnknownAction(String),
+    // A key that couldn't be parsed, as written
+    InvalidKey(String),
+    // One key bound to two different actions
+    Conflict { key: Key, first: Action, second: Action },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::UnknownAction(name) => {
+                let names: Vec<&str> = Action::ALL.iter().map(Action::name).collect();
+                write!(f, "unknown action `{}` in [keys]; expected one of {}", name, names.join(", "))
+            }
+            KeymapError::InvalidKey(key) => write!(f, "invalid key `{}` in [keys]", key),
+            KeymapError::Conflict { key, first, second } => {
+                write!(f, "key `{}` is bound to both `{}` and `{}`", key, first, second)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+// The `[keys]` table: action name -> keys
+pub type KeyTable = BTreeMap<String, Vec<String>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    // In the order the keys were listed, so hints show the preferred key first
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::from_table(&KeyTable::new()).expect("default keys are valid")
+    }
+}
+
+impl Keymap {
+    // The defaults with every action named in `table` rebound to the listed keys
+    pub fn from_table(table: &KeyTable) -> Result<Keymap, KeymapError> {
+        let mut overrides = HashMap::new();
+        for (name, keys) in table {
+            overrides.insert(name.parse::<Action>()?, keys);
+        }
+
+        let mut bindings: Vec<(Key, Action)> = Vec::new();
+        for action in Action::ALL {
+            let keys: Vec<Key> = match overrides.get(&action) {
+                Some(keys) => keys.iter().map(|key| key.parse()).collect::<Result<_, _>>()?,
+                None => action.default_keys().iter().map(|key| key.parse()).collect::<Result<_, _>>()?,
+            };
+            for key in keys {
+                match bindings.iter().find(|(bound, _)| *bound == key) {
+                    Some((_, first)) if *first != action => {
+                        return Err(KeymapError::Conflict { key, first: *first, second: action });
+                    }
+                    Some(_) => {}
+                    None => bindings.push((key, action)),
+                }
+            }
+        }
+        Ok(Keymap { bindings })
+    }
+
+    pub fn action(&self, key: KeyEvent) -> Option<Action> {
+        let key = Key::from(key);
+        self.bindings.iter().find(|(bound, _)| *bound == key).map(|(_, action)| *action)
+    }
+
+    // Keys bound to `action`, in the order they were listed
+    pub fn keys(&self, action: Action) -> Vec<Key> {
+        self.bindings.iter().filter(|(_, a)| *a == action).map(|(key, _)| *key).collect()
+    }
+
+    // The keys for `action` as shown in hints, e.g. "j/down"; "unbound" when there are none
+    pub fn describe_keys(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(Key::to_string).collect();
+        if keys.is_empty() {
+            String::from("unbound")
+        } else {
+            keys.join("/")
+        }
+    }
+
+    // Every binding as a `[keys]` table, for printing the effective config
+    pub fn to_table(&self) -> KeyTable {
+        Action::ALL
+            .iter()
+            .map(|action| (action.name().to_string(), self.keys(*action).iter().map(Key::to_string).collect()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn table(entries: &[(&str, &[&str])]) -> KeyTable {
+        entries
+            .iter()
+            .map(|(action, keys)| (action.to_string(), keys.iter().map(|key| key.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn defaults_have_vim_keys_and_arrows() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(press(KeyCode::Char('j'), KeyModifiers::NONE)), Some(Action::Down));
+        assert_eq!(keymap.action(press(KeyCode::Down, KeyModifiers::NONE)), Some(Action::Down));
+        assert_eq!(keymap.action(press(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(keymap.action(press(KeyCode::Char('x'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn shifted_letters_match_their_uppercase_binding() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(press(KeyCode::Char('L'), KeyModifiers::SHIFT)), Some(Action::ScreenBottom));
+        assert_eq!(keymap.action(press(KeyCode::Char('L'), KeyModifiers::NONE)), Some(Action::ScreenBottom));
+        assert_eq!(keymap.action(press(KeyCode::Char('l'), KeyModifiers::NONE)), Some(Action::Right));
+    }
+
+    #[test]
+    fn keys_parse_and_print_back() {
+        for text in ["q", "L", "ctrl-z", "alt-enter", "f5", "pagedown", "space", "1"] {
+            assert_eq!(text.parse::<Key>().unwrap().to_string(), text);
+        }
+        assert_eq!("Ctrl-Z".parse::<Key>().unwrap().to_string(), "ctrl-z");
+        assert_eq!("shift-l".parse::<Key>().unwrap().to_string(), "L");
+        assert_eq!("-".parse::<Key>().unwrap().code, KeyCode::Char('-'));
+        assert!("hyper-x".parse::<Key>().is_err());
+        assert!("f13".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn hints_list_keys_in_configured_order() {
+        assert_eq!(Keymap::default().describe_keys(Action::Down), "j/down");
+        let keymap = Keymap::from_table(&table(&[("down", &["down", "t"]), ("help", &[])])).unwrap();
+        assert_eq!(keymap.describe_keys(Action::Down), "down/t");
+        assert_eq!(keymap.describe_keys(Action::Help), "unbound");
+    }
+
+    #[test]
+    fn listed_actions_replace_their_defaults() {
+        let keymap = Keymap::from_table(&table(&[("down", &["t"])])).unwrap();
+        assert_eq!(keymap.action(press(KeyCode::Char('t'), KeyModifiers::NONE)), Some(Action::Down));
+        assert_eq!(keymap.action(press(KeyCode::Char('j'), KeyModifiers::NONE)), None);
+        assert_eq!(keymap.action(press(KeyCode::Char('k'), KeyModifiers::NONE)), Some(Action::Up));
+    }
+
+    #[test]
+    fn conflicts_are_reported() {
+        let err = Keymap::from_table(&table(&[("switch-version", &["j"])])).unwrap_err();
+        assert_eq!(err.to_string(), "key `j` is bound to both `down` and `switch-version`");
+    }
+
+    #[test]
+    fn unknown_actions_and_keys_are_reported() {
+        let err = Keymap::from_table(&table(&[("jump", &["g"])])).unwrap_err();
+        assert!(err.to_string().starts_with("unknown action `jump` in [keys]; expected one of quit, up"));
+        let err = Keymap::from_table(&table(&[("quit", &["ctrl-"])])).unwrap_err();
+        assert_eq!(err.to_string(), "invalid key `ctrl-` in [keys]");
+    }
+
+    #[test]
+    fn effective_table_round_trips() {
+        let keymap = Keymap::from_table(&table(&[("quit", &["ctrl-q"])])).unwrap();
+        assert_eq!(Keymap::from_table(&keymap.to_table()).unwrap(), keymap);
+    }
+}
@@ -0,0 +1,412 @@
+// Vim motions for every list in py_manager2:
+// counts (`10j`), `gg`/`G`, ctrl-d/ctrl-u, `H`/`M`/`L` and marks (`ma`, `'a`).
+// A `Navigator` turns keys into `Motion`s, which may take several keys, and
+// `apply` moves any list that implements `Rows`. The motion keys come from the
+// keymap like every other key; digits are always counts and the letter after `m`
+// or `'` is always a mark name.
+//
+// A plain `j` or `k` is left to the frontend, so each list keeps its own idea of
+// what happens at the ends; with a count the cursor stops there, as in vim.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use keymap::{Action, Keymap};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// Counts past this are taken as this
+const MAX_COUNT: usize = 9999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Down(usize),
+    Up(usize),
+    PageDown(usize),
+    PageUp(usize),
+    HalfPageDown(usize),
+    HalfPageUp(usize),
+    First,
+    Last,
+    // `5G` or `5gg`, counted from 0
+    Row(usize),
+    // The Nth row from the top or bottom of the screen
+    ScreenTop(usize),
+    ScreenMiddle,
+    ScreenBottom(usize),
+    SetMark(char),
+    JumpToMark(char),
+}
+
+// What a key did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Motion(Motion),
+    // Taken as part of a motion that isn't finished, or that was cancelled
+    Consumed,
+    // Not a motion key; any count typed before it is dropped
+    Other,
+}
+
+// Keys waiting for the one that finishes the motion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    // The first `g` of `gg`; the same letter has to follow
+    Top(char),
+    SetMark,
+    JumpToMark,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Navigator {
+    count: Option<usize>,
+    pending: Option<Pending>,
+    // What has been typed so far, for the footer
+    typed: String,
+}
+
+impl Navigator {
+    pub fn handle_key(&mut self, key: KeyEvent, keymap: &Keymap) -> Step {
+        let was_pending = self.is_pending();
+        let letter = match key.code {
+            KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => Some(c),
+            _ => None,
+        };
+        match (self.pending.take(), letter) {
+            (Some(Pending::SetMark), Some(name)) if name.is_ascii_alphabetic() => return self.finish(Motion::SetMark(name)),
+            (Some(Pending::JumpToMark), Some(name)) if name.is_ascii_alphabetic() => {
+                return self.finish(Motion::JumpToMark(name))
+            }
+            (Some(Pending::SetMark | Pending::JumpToMark), _) => {
+                self.reset();
+                return Step::Consumed;
+            }
+            (Some(Pending::Top(first)), Some(second)) if first == second => {
+                let motion = self.row_or(Motion::First);
+                return self.finish(motion);
+            }
+            // Anything else after `g` starts over with this key
+            (Some(Pending::Top(_)), _) => self.reset(),
+            (None, _) => {}
+        }
+
+        if let (Some(digit @ '0'..='9'), true) = (letter, key.modifiers.is_empty()) {
+            // A leading 0 isn't a count
+            if digit != '0' || self.count.is_some() {
+                let count = self.count.unwrap_or(0) * 10 + digit as usize - '0' as usize;
+                self.count = Some(count.min(MAX_COUNT));
+                self.typed.push(digit);
+                return Step::Consumed;
+            }
+        }
+
+        let count = self.count.unwrap_or(1);
+        let motion = match keymap.action(key) {
+            Some(Action::Top) => match letter {
+                Some(first) => return self.wait(Pending::Top(first), first),
+                None => self.row_or(Motion::First),
+            },
+            Some(Action::Bottom) => self.row_or(Motion::Last),
+            Some(Action::HalfPageDown) => Motion::HalfPageDown(count),
+            Some(Action::HalfPageUp) => Motion::HalfPageUp(count),
+            Some(Action::ScreenTop) => Motion::ScreenTop(count),
+            Some(Action::ScreenMiddle) => Motion::ScreenMiddle,
+            Some(Action::ScreenBottom) => Motion::ScreenBottom(count),
+            Some(Action::SetMark) => return self.wait(Pending::SetMark, letter.unwrap_or('m')),
+            Some(Action::JumpToMark) => return self.wait(Pending::JumpToMark, letter.unwrap_or('\'')),
+            Some(Action::Down) if self.count.is_some() => Motion::Down(count),
+            Some(Action::Up) if self.count.is_some() => Motion::Up(count),
+            Some(Action::PageDown) if self.count.is_some() => Motion::PageDown(count),
+            Some(Action::PageUp) if self.count.is_some() => Motion::PageUp(count),
+            // Esc only drops what was typed
+            Some(Action::Cancel) if was_pending => {
+                self.reset();
+                return Step::Consumed;
+            }
+            _ => {
+                self.reset();
+                return Step::Other;
+            }
+        };
+        self.finish(motion)
+    }
+
+    // Keys of an unfinished motion, like vim's showcmd: "10", "g", "m"
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.typed.is_empty()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Navigator::default();
+    }
+
+    fn row_or(&self, motion: Motion) -> Motion {
+        self.count.map_or(motion, |row| Motion::Row(row - 1))
+    }
+
+    fn wait(&mut self, pending: Pending, key: char) -> Step {
+        self.pending = Some(pending);
+        self.typed.push(key);
+        Step::Consumed
+    }
+
+    fn finish(&mut self, motion: Motion) -> Step {
+        self.reset();
+        Step::Motion(motion)
+    }
+}
+
+// The keys for a motion action as shown in hints: "gg/home" for a letter that's
+// pressed twice, "m{a-z}" for the mark keys
+pub fn describe_keys(keymap: &Keymap, action: Action) -> String {
+    let keys: Vec<String> = keymap
+        .keys(action)
+        .iter()
+        .map(|key| match (action, key.to_string()) {
+            (Action::Top, key) if key.chars().count() == 1 => key.repeat(2),
+            (Action::SetMark | Action::JumpToMark, key) => format!("{}{{a-z}}", key),
+            (_, key) => key,
+        })
+        .collect();
+    if keys.is_empty() {
+        String::from("unbound")
+    } else {
+        keys.join("/")
+    }
+}
+
+// Every motion a list takes, for help overlays
+pub const MOTIONS: [Action; 9] = [
+    Action::Top,
+    Action::Bottom,
+    Action::HalfPageDown,
+    Action::HalfPageUp,
+    Action::ScreenTop,
+    Action::ScreenMiddle,
+    Action::ScreenBottom,
+    Action::SetMark,
+    Action::JumpToMark,
+];
+
+// Letter -> the text of the marked row, so a mark still finds its row after the
+// list is reloaded or filtered
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Marks(BTreeMap<char, String>);
+
+impl Marks {
+    pub fn set(&mut self, name: char, row: String) {
+        self.0.insert(name, row);
+    }
+
+    pub fn get(&self, name: char) -> Option<&str> {
+        self.0.get(&name).map(String::as_str)
+    }
+}
+
+// A list motions can move through. Rows are what's on screen, in order, so a
+// filtered list only counts its matches.
+pub trait Rows {
+    fn row_count(&self) -> usize;
+    fn selected_row(&self) -> Option<usize>;
+    fn select_row(&mut self, row: usize);
+    // The first row on screen as of the last frame
+    fn first_visible_row(&self) -> usize;
+    fn row_text(&self, row: usize) -> &str;
+    fn marks(&mut self) -> &mut Marks;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MotionError {
+    MarkNotSet(char),
+    // The marked row isn't in the list anymore
+    MarkGone { name: char, row: String },
+}
+
+impl fmt::Display for MotionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MotionError::MarkNotSet(name) => write!(f, "mark `{}` isn't set in this list", name),
+            MotionError::MarkGone { name, row } => write!(f, "`{}` (mark `{}`) is no longer in the list", row, name),
+        }
+    }
+}
+
+impl std::error::Error for MotionError {}
+
+// Move the selection of a list whose window shows `height` rows. True when it moved.
+pub fn apply(rows: &mut impl Rows, motion: Motion, height: usize) -> Result<bool, MotionError> {
+    let len = rows.row_count();
+    if len == 0 {
+        return Ok(false);
+    }
+    let last = len - 1;
+    let current = rows.selected_row().unwrap_or(0).min(last);
+    let page = height.max(1);
+    let half_page = (page / 2).max(1);
+    let top = rows.first_visible_row().min(last);
+    let bottom = (top + page).min(len) - 1;
+
+    let target = match motion {
+        Motion::Down(n) => current.saturating_add(n),
+        Motion::Up(n) => current.saturating_sub(n),
+        Motion::PageDown(n) => current.saturating_add(n.saturating_mul(page)),
+        Motion::PageUp(n) => current.saturating_sub(n.saturating_mul(page)),
+        Motion::HalfPageDown(n) => current.saturating_add(n.saturating_mul(half_page)),
+        Motion::HalfPageUp(n) => current.saturating_sub(n.saturating_mul(half_page)),
+        Motion::First => 0,
+        Motion::Last => last,
+        Motion::Row(row) => row,
+        Motion::ScreenTop(n) => (top + n.saturating_sub(1)).min(bottom),
+        Motion::ScreenMiddle => top + (bottom - top) / 2,
+        Motion::ScreenBottom(n) => bottom.saturating_sub(n.saturating_sub(1)).max(top),
+        Motion::SetMark(name) => {
+            let row = rows.row_text(current).to_string();
+            rows.marks().set(name, row);
+            return Ok(false);
+        }
+        Motion::JumpToMark(name) => {
+            let row = rows.marks().get(name).ok_or(MotionError::MarkNotSet(name))?.to_string();
+            match (0..len).find(|&i| rows.row_text(i) == row) {
+                Some(i) => i,
+                None => return Err(MotionError::MarkGone { name, row }),
+            }
+        }
+    }
+    .min(last);
+    let moved = rows.selected_row() != Some(target);
+    rows.select_row(target);
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct List {
+        items: Vec<String>,
+        selected: Option<usize>,
+        offset: usize,
+        marks: Marks,
+    }
+
+    impl Rows for List {
+        fn row_count(&self) -> usize {
+            self.items.len()
+        }
+
+        fn selected_row(&self) -> Option<usize> {
+            self.selected
+        }
+
+        fn select_row(&mut self, row: usize) {
+            self.selected = Some(row);
+        }
+
+        fn first_visible_row(&self) -> usize {
+            self.offset
+        }
+
+        fn row_text(&self, row: usize) -> &str {
+            &self.items[row]
+        }
+
+        fn marks(&mut self) -> &mut Marks {
+            &mut self.marks
+        }
+    }
+
+    fn list(len: usize) -> List {
+        List { items: (0..len).map(|i| format!("row {}", i)).collect(), selected: Some(0), offset: 0, marks: Marks::default() }
+    }
+
+    // Feed `keys` (one character each) and return the motion the last one finished
+    fn motion(keys: &str) -> Option<Motion> {
+        let keymap = Keymap::default();
+        let mut navigator = Navigator::default();
+        let mut last = None;
+        for c in keys.chars() {
+            last = match navigator.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), &keymap) {
+                Step::Motion(motion) => Some(motion),
+                _ => None,
+            };
+        }
+        last
+    }
+
+    #[test]
+    fn keys_become_motions() {
+        assert_eq!(motion("10j"), Some(Motion::Down(10)));
+        assert_eq!(motion("3k"), Some(Motion::Up(3)));
+        assert_eq!(motion("j"), None);
+        assert_eq!(motion("gg"), Some(Motion::First));
+        assert_eq!(motion("5gg"), Some(Motion::Row(4)));
+        assert_eq!(motion("G"), Some(Motion::Last));
+        assert_eq!(motion("12G"), Some(Motion::Row(11)));
+        assert_eq!(motion("2L"), Some(Motion::ScreenBottom(2)));
+        assert_eq!(motion("ma"), Some(Motion::SetMark('a')));
+        assert_eq!(motion("'a"), Some(Motion::JumpToMark('a')));
+        // A leading zero and a broken-off `g` aren't motions
+        assert_eq!(motion("0j"), None);
+        assert_eq!(motion("gG"), Some(Motion::Last));
+    }
+
+    #[test]
+    fn unfinished_motions_are_shown_and_cancelled() {
+        let keymap = Keymap::default();
+        let mut navigator = Navigator::default();
+        for c in "12g".chars() {
+            navigator.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), &keymap);
+        }
+        assert_eq!(navigator.typed(), "12g");
+        assert_eq!(navigator.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE), &keymap), Step::Consumed);
+        assert!(!navigator.is_pending());
+        assert_eq!(navigator.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE), &keymap), Step::Other);
+        assert_eq!(navigator.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL), &keymap), Step::Motion(Motion::HalfPageDown(1)));
+    }
+
+    #[test]
+    fn motions_stop_at_the_ends_and_the_screen() {
+        let mut rows = list(100);
+        assert!(apply(&mut rows, Motion::Down(10), 20).unwrap());
+        assert_eq!(rows.selected, Some(10));
+        apply(&mut rows, Motion::HalfPageDown(1), 20).unwrap();
+        assert_eq!(rows.selected, Some(20));
+        apply(&mut rows, Motion::Down(500), 20).unwrap();
+        assert_eq!(rows.selected, Some(99));
+        assert!(!apply(&mut rows, Motion::Last, 20).unwrap());
+
+        rows.offset = 30;
+        apply(&mut rows, Motion::ScreenTop(1), 20).unwrap();
+        assert_eq!(rows.selected, Some(30));
+        apply(&mut rows, Motion::ScreenMiddle, 20).unwrap();
+        assert_eq!(rows.selected, Some(39));
+        apply(&mut rows, Motion::ScreenBottom(3), 20).unwrap();
+        assert_eq!(rows.selected, Some(47));
+
+        // A short list ends before the screen does
+        let mut rows = list(5);
+        apply(&mut rows, Motion::ScreenBottom(1), 20).unwrap();
+        assert_eq!(rows.selected, Some(4));
+        assert!(!apply(&mut list(0), Motion::Last, 20).unwrap());
+    }
+
+    #[test]
+    fn marks_find_their_row_after_the_list_changes() {
+        let mut rows = list(10);
+        rows.selected = Some(7);
+        apply(&mut rows, Motion::SetMark('a'), 20).unwrap();
+        apply(&mut rows, Motion::First, 20).unwrap();
+        rows.items.remove(2);
+        assert!(apply(&mut rows, Motion::JumpToMark('a'), 20).unwrap());
+        assert_eq!(rows.selected, Some(6));
+
+        assert_eq!(apply(&mut rows, Motion::JumpToMark('b'), 20).unwrap_err().to_string(), "mark `b` isn't set in this list");
+        rows.items.retain(|item| item != "row 7");
+        assert_eq!(
+            apply(&mut rows, Motion::JumpToMark('a'), 20).unwrap_err(),
+            MotionError::MarkGone { name: 'a', row: String::from("row 7") }
+        );
+    }
+}
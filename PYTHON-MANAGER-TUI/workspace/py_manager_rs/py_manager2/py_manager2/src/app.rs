@@ -0,0 +1,1638 @@
+use crossterm::event::{Event as TermEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use keymap::{Action, Keymap};
+use navigation::{Motion, Navigator, Step, MOTIONS};
+use py_manager_core::cache::{Freshness, Inventory};
+use py_manager_core::operations::Scope;
+use py_manager_core::packages::{self, clean_version};
+use py_manager_core::site_packages::Interpreter;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use theme::Theme;
+use tracing::{debug, error, info, warn, Level};
+use tui::layout::Rect;
+
+use crate::command::{self, Command, CommandLine, HISTORY_LIMIT};
+use crate::config::{Config, Layout, Split};
+use crate::event::Event;
+use crate::list::StatefulList;
+use crate::logging::LogLine;
+use crate::modal::{Choice, Intent, Job, Modal, Outcome, Prompt};
+use crate::packages::Known;
+use crate::session::{SavedSearch, Session};
+use crate::ui::{self, Areas, Pane};
+use crate::watch::FsChange;
+
+// Background work the app asks for; the results come back as events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Task {
+    RefreshPackages { version: String, known: Option<Known> },
+    RefreshCurrentVersion,
+    // Re-read the environment and virtualenv shown in the status box
+    RefreshStatus,
+    RefreshVersions,
+    // Point the file watcher at the site-packages of these versions
+    WatchSitePackages(Vec<String>),
+    // Find where these versions live on disk, for their pages
+    InspectInterpreters(Vec<String>),
+    SearchPyPI(String),
+    // Read the versions pyenv can install
+    ListAvailable,
+    Install(String),
+    Uninstall(String),
+    Switch { version: String, scope: Scope },
+    CreateVenv { version: String, path: PathBuf },
+    // Write the command history back to disk
+    SaveHistory(Vec<String>),
+    // Remember the pane layout for the next session
+    SaveLayout(Layout),
+    // Send `Event::ToastTimeout` after this long
+    ExpireToasts(Duration),
+    // Hand the terminal back to the shell until the job is resumed
+    Suspend,
+}
+
+// Packages that differ between two versions, from `:diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageDiff {
+    pub left: String,
+    pub right: String,
+    pub lines: Vec<String>,
+}
+
+// The list that the cursor keys, the motions and `/` act on, or that a click landed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListId {
+    Versions,
+    // The selected version's packages, on its page
+    Installed,
+    PyPI,
+    // The versions pyenv can install
+    Available,
+    Log,
+}
+
+impl ListId {
+    pub const ALL: [ListId; 5] = [ListId::Versions, ListId::Installed, ListId::PyPI, ListId::Available, ListId::Log];
+
+    // The name its search is saved under in the session
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListId::Versions => "versions",
+            ListId::Installed => "installed",
+            ListId::PyPI => "pypi",
+            ListId::Available => "available",
+            ListId::Log => "log",
+        }
+    }
+}
+
+// A `/` query being typed into a list, and where its cursor was before, to go
+// back to if the search is cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchInput {
+    pub list: ListId,
+    origin: Option<usize>,
+}
+
+// Log lines kept for the log panel
+pub const LOG_HISTORY: usize = 500;
+
+// A transient notification in the corner of the screen
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub level: Level,
+    pub message: String,
+    pub expires: Instant,
+}
+
+// One entry of the help overlay and footer: what a key does in the current pane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    // None for keys that bypass the keymap, like those of the query editor
+    pub action: Option<Action>,
+    pub keys: String,
+    pub description: &'static str,
+}
+
+pub struct App {
+    // One tab per version, in the order the tabs are shown
+    pub versions: StatefulList<String>,
+    // Versions whose tabs are pinned to the front, by bare name
+    pub pinned: HashSet<String>,
+    pub interpreters: HashMap<String, Interpreter>,
+    // Keyed by the bare version name, without `pyenv versions` annotations
+    pub package_cache: HashMap<String, Inventory>,
+    // The selected version's packages as shown on its page, and whose they are
+    pub installed: StatefulList<String>,
+    installed_version: Option<String>,
+    // Package to select on the page once the restored version's packages are read
+    restored_installed: Option<String>,
+    // The cursor keys move through the page's packages instead of the versions
+    pub packages_focused: bool,
+    // Versions with a refresh task in flight
+    refreshing: HashSet<String>,
+    // Versions that changed again while their refresh was in flight, and whether
+    // the next refresh must re-read them regardless of their fingerprint
+    requeued: HashMap<String, bool>,
+    // Why a version's packages couldn't be read, for versions with nothing cached
+    pub package_errors: HashMap<String, String>,
+    pub current_python_version: String,
+    pub python_env: String,
+    pub virtual_env: String,
+    pub show_pypi: bool,
+    // What the PyPI list was last searched for
+    pub pypi_query: String,
+    pub pypi_packages: StatefulList<String>,
+    // Package to select once the restored search comes back
+    restored_package: Option<String>,
+    pub show_available: bool,
+    // The versions pyenv can install, less those the config hides; read when the
+    // pane is first opened, and again after a failure
+    pub available: StatefulList<String>,
+    available_requested: bool,
+    // Why they couldn't be read
+    pub available_error: Option<String>,
+    // Open dialogs, the one on top last; it takes all input
+    pub modals: Vec<Modal>,
+    // Some while the user is typing a `:` command
+    pub command_line: Option<CommandLine>,
+    // Some while the user is typing a `/` query
+    pub searching: Option<SearchInput>,
+    // Commands run so far, oldest first, including earlier sessions
+    pub history: Vec<String>,
+    pub diff: Option<PackageDiff>,
+    // Last failure, shown in the status area until dismissed with Esc
+    pub error_message: Option<String>,
+    pub toasts: Vec<Toast>,
+    // Recent log lines, oldest first
+    pub log_lines: VecDeque<LogLine>,
+    // Those the log panel shows, one row each, with a cursor to search them with
+    pub log: StatefulList<String>,
+    pub show_log: bool,
+    pub show_help: bool,
+    // The help overlay's lines, scrolled like the other lists
+    pub help: StatefulList<String>,
+    // Most verbose level the log panel shows
+    pub log_filter: Level,
+    pub config: Config,
+    // Pane sizes and split, from the config or as last saved, changed from the keyboard
+    pub layout: Layout,
+    // The focused pane takes the whole screen between the tabs and the footer
+    pub zoom: bool,
+    pub keymap: Keymap,
+    // Counts, `gg`, marks and the other motions of the lists
+    pub navigator: Navigator,
+    pub theme: Theme,
+    // Terminal size, to lay the panes out for mouse hit-testing
+    pub screen: Rect,
+    pub should_quit: bool,
+    // Set whenever state changed since the last frame was drawn
+    pub dirty: bool,
+}
+
+impl App {
+    pub fn new(
+        versions: Vec<String>,
+        package_cache: HashMap<String, Inventory>,
+        current_python_version: String,
+        python_env: String,
+        virtual_env: String,
+    ) -> App {
+        let mut app = App {
+            versions: StatefulList::with_items(versions),
+            pinned: HashSet::new(),
+            interpreters: HashMap::new(),
+            package_cache,
+            installed: StatefulList::default(),
+            installed_version: None,
+            restored_installed: None,
+            packages_focused: false,
+            refreshing: HashSet::new(),
+            requeued: HashMap::new(),
+            package_errors: HashMap::new(),
+            current_python_version,
+            python_env,
+            virtual_env,
+            show_pypi: false,
+            pypi_query: String::new(),
+            pypi_packages: StatefulList::default(),
+            restored_package: None,
+            show_available: false,
+            available: StatefulList::default(),
+            available_requested: false,
+            available_error: None,
+            modals: Vec::new(),
+            command_line: None,
+            searching: None,
+            history: Vec::new(),
+            diff: None,
+            error_message: None,
+            toasts: Vec::new(),
+            log_lines: VecDeque::new(),
+            log: StatefulList::default(),
+            show_log: false,
+            show_help: false,
+            help: StatefulList::default(),
+            log_filter: Level::TRACE,
+            config: Config::default(),
+            layout: Layout::default(),
+            zoom: false,
+            keymap: Keymap::default(),
+            navigator: Navigator::default(),
+            theme: Theme::default(),
+            screen: Rect::default(),
+            should_quit: false,
+            dirty: true,
+        };
+        app.sync_installed();
+        app
+    }
+
+    // Apply user settings, dropping any versions the config hides
+    pub fn with_config(mut self, config: Config) -> App {
+        self.versions.items.retain(|v| config.filters.installed_visible(v));
+        self.versions.clamp();
+        self.keymap = config.keymap();
+        self.theme = config.theme();
+        self.layout = config.layout;
+        self.config = config;
+        self.sync_installed();
+        self
+    }
+
+    // Where everything is on the current screen
+    pub fn areas(&self) -> Areas {
+        Areas::new(self.screen, self.layout, self.focused_pane(), self.zoom, ui::status_lines(self))
+    }
+
+    // The panel while it shows something, otherwise the version list
+    pub fn focused_pane(&self) -> Pane {
+        if self.show_pypi || self.show_available || self.show_log || self.diff.is_some() {
+            Pane::Panel
+        } else {
+            Pane::Versions
+        }
+    }
+
+    // The PyPI list, the available versions or the log while the panel shows one,
+    // otherwise the versions or their packages; a diff leaves the keys with the versions
+    pub fn focused_list(&self) -> ListId {
+        if self.show_pypi {
+            ListId::PyPI
+        } else if self.show_available {
+            ListId::Available
+        } else if self.show_log && self.diff.is_none() {
+            ListId::Log
+        } else if self.packages_focused {
+            ListId::Installed
+        } else {
+            ListId::Versions
+        }
+    }
+
+    pub fn list(&self, id: ListId) -> &StatefulList<String> {
+        match id {
+            ListId::Versions => &self.versions,
+            ListId::Installed => &self.installed,
+            ListId::PyPI => &self.pypi_packages,
+            ListId::Available => &self.available,
+            ListId::Log => &self.log,
+        }
+    }
+
+    fn list_mut(&mut self, id: ListId) -> &mut StatefulList<String> {
+        match id {
+            ListId::Versions => &mut self.versions,
+            ListId::Installed => &mut self.installed,
+            ListId::PyPI => &mut self.pypi_packages,
+            ListId::Available => &mut self.available,
+            ListId::Log => &mut self.log,
+        }
+    }
+
+    pub fn zoomed_pane(&self) -> Option<Pane> {
+        self.zoom.then(|| self.focused_pane())
+    }
+
+    pub fn selected_version(&self) -> Option<&String> {
+        self.versions.selected_item()
+    }
+
+    pub fn selected_inventory(&self) -> Option<&Inventory> {
+        self.package_cache.get(&clean_version(self.selected_version()?))
+    }
+
+    pub fn selected_package_error(&self) -> Option<&String> {
+        self.package_errors.get(&clean_version(self.selected_version()?))
+    }
+
+    pub fn report_error(&mut self, message: String) {
+        warn!("{}", message);
+        self.error_message = Some(message);
+        self.dirty = true;
+    }
+
+    // Show a toast and record the message in the log
+    pub fn notify(&mut self, level: Level, message: String) -> Vec<Task> {
+        match level {
+            Level::ERROR => error!("{}", message),
+            Level::WARN => warn!("{}", message),
+            Level::INFO => info!("{}", message),
+            _ => debug!("{}", message),
+        }
+        let duration = self.config.timing.toast_duration();
+        self.toasts.push(Toast {
+            level,
+            message,
+            expires: Instant::now() + duration,
+        });
+        self.dirty = true;
+        vec![Task::ExpireToasts(duration)]
+    }
+
+    // Log lines at or above the panel's filter level
+    pub fn visible_log_lines(&self) -> impl Iterator<Item = &LogLine> {
+        let filter = self.log_filter;
+        self.log_lines.iter().filter(move |line| line.level <= filter)
+    }
+
+    // Rebuild the log panel's rows after a new line or another level filter. A
+    // cursor on the newest line follows the new ones in, like `tail -f`; anywhere
+    // else it stays on its line.
+    fn sync_log(&mut self) {
+        let rows: Vec<String> = self
+            .visible_log_lines()
+            .map(|line| format!("{} {:<5} {}", line.time, line.level, line.message))
+            .collect();
+        let following = self.log.selected().is_none() || self.log.selected() == self.log.shown().last().copied();
+        let kept = self.log.selected_item().cloned();
+        self.log.items = rows;
+        match kept.and_then(|kept| self.log.items.iter().rposition(|row| *row == kept)).filter(|_| !following) {
+            Some(index) => {
+                self.log.select(index);
+            }
+            None => {
+                if let Some(&last) = self.log.shown().last() {
+                    self.log.select(last);
+                }
+            }
+        }
+        self.log.clamp();
+    }
+
+    // What to save so the next start looks like this one
+    pub fn session(&self) -> Session {
+        let mut pinned: Vec<String> = self.pinned.iter().cloned().collect();
+        pinned.sort();
+        let searches = ListId::ALL
+            .iter()
+            .map(|&id| (id.name(), &self.list(id).search))
+            .filter(|(_, search)| !search.is_empty())
+            .map(|(name, search)| (name.to_string(), SavedSearch { query: search.query.clone(), filter: search.filter }))
+            .collect();
+        Session {
+            tabs: self.versions.items.iter().map(|v| clean_version(v)).collect(),
+            pinned,
+            version: self.selected_version().map(|v| clean_version(v)),
+            installed_package: self.installed.selected_item().and_then(|line| package_name(line)),
+            packages_focused: self.packages_focused,
+            show_pypi: self.show_pypi,
+            query: self.pypi_query.clone(),
+            package: self.pypi_packages.selected_item().cloned(),
+            show_available: self.show_available,
+            show_log: self.show_log,
+            log_filter: self.log_filter.to_string().to_lowercase(),
+            zoom: self.zoom,
+            searches,
+        }
+    }
+
+    // Put back a saved session over the discovered versions. Tabs keep their saved
+    // order with new versions after them; the PyPI list is searched again and the
+    // available versions read again, and the selected packages are picked once
+    // they are listed. Each list gets its search back, and rows read in later are
+    // filtered by it as they arrive.
+    pub fn restore(&mut self, session: Session) -> Vec<Task> {
+        self.pinned = session.pinned.into_iter().collect();
+        let mut versions = self.versions.items.clone();
+        versions.sort_by_key(|version| {
+            let name = clean_version(version);
+            (!self.pinned.contains(&name), session.tabs.iter().position(|tab| *tab == name).unwrap_or(usize::MAX))
+        });
+        self.versions.set_items(versions);
+        if let Some(index) = session.version.and_then(|saved| self.versions.items.iter().position(|v| clean_version(v) == saved)) {
+            self.versions.select(index);
+        }
+        self.show_log = session.show_log;
+        if let Ok(level) = session.log_filter.parse() {
+            self.log_filter = level;
+        }
+        self.zoom = session.zoom;
+        for id in ListId::ALL {
+            if let Some(saved) = session.searches.get(id.name()) {
+                let list = self.list_mut(id);
+                list.search.query = saved.query.clone();
+                list.search.filter = saved.filter;
+                list.clamp();
+            }
+        }
+        self.dirty = true;
+        self.sync_installed();
+        self.restored_installed = session.installed_package;
+        self.reselect_installed();
+        self.packages_focused = session.packages_focused;
+        self.show_available = false;
+        let mut tasks = if session.show_available { self.toggle_available() } else { Vec::new() };
+        if session.show_pypi && self.config.backends.pypi {
+            self.restored_package = session.package;
+            tasks.extend(self.open_pypi(session.query));
+        }
+        tasks
+    }
+
+    // Revalidate every inventory, the selected version first, and start watching
+    pub fn startup_tasks(&mut self) -> Vec<Task> {
+        let mut tasks = self.refresh_all_packages();
+        tasks.push(Task::WatchSitePackages(self.versions.items.clone()));
+        tasks.push(self.inspect_interpreters());
+        tasks
+    }
+
+    fn inspect_interpreters(&self) -> Task {
+        Task::InspectInterpreters(self.versions.items.iter().map(|v| clean_version(v)).collect())
+    }
+
+    // A one-line verdict on the selected version for its page, with how alarming it is
+    pub fn health(&self) -> Option<(Level, String)> {
+        let version = clean_version(self.selected_version()?);
+        if let Some(error) = self.package_errors.get(&version) {
+            return Some((Level::ERROR, format!("packages can't be read: {}", error)));
+        }
+        if let Some(Interpreter { prefix: Some(prefix), executable, site_packages }) = self.interpreters.get(&version) {
+            if executable.is_none() {
+                return Some((Level::ERROR, format!("no python binary in {}", prefix.join("bin").display())));
+            }
+            if site_packages.is_empty() {
+                return Some((Level::WARN, String::from("no site-packages directory")));
+            }
+        }
+        Some(match self.package_cache.get(&version) {
+            Some(inventory) => (Level::INFO, format!("ok, {} packages", inventory.packages.len())),
+            None => (Level::DEBUG, String::from("reading packages...")),
+        })
+    }
+
+    fn refresh_all_packages(&mut self) -> Vec<Task> {
+        let first = self.versions.selected().unwrap_or(0);
+        let order: Vec<usize> = std::iter::once(first)
+            .chain((0..self.versions.len()).filter(|&i| i != first))
+            .filter(|&i| i < self.versions.len())
+            .collect();
+        order
+            .into_iter()
+            .filter_map(|i| self.request_packages_at(i))
+            .collect()
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Vec<Task> {
+        let tasks = self.dispatch(event);
+        self.sync_installed();
+        tasks
+    }
+
+    // Keep the page's package list on the selected version's inventory; the cursor
+    // stays on its package when the same version is read again, and a package left
+    // to restore is dropped once another version is selected
+    fn sync_installed(&mut self) {
+        let version = self.selected_version().map(|v| clean_version(v));
+        if version != self.installed_version {
+            self.restored_installed = None;
+        }
+        let packages = self.selected_inventory().map(|inventory| inventory.packages.as_slice()).unwrap_or_default();
+        if version == self.installed_version && packages == self.installed.items.as_slice() {
+            return;
+        }
+        let packages = packages.to_vec();
+        let kept = self.installed.selected_item().filter(|_| version == self.installed_version).and_then(|line| package_name(line));
+        self.installed.set_items(packages);
+        self.installed_version = version;
+        match kept {
+            Some(kept) => {
+                self.select_installed(&kept);
+            }
+            None => self.reselect_installed(),
+        }
+        self.dirty = true;
+    }
+
+    // Put the page's cursor on the package with this name; false when it isn't listed
+    fn select_installed(&mut self, name: &str) -> bool {
+        match self.installed.items.iter().position(|line| package_name(line).as_deref() == Some(name)) {
+            Some(index) => self.installed.select(index),
+            None => false,
+        }
+    }
+
+    // Select the restored session's package once it is listed
+    fn reselect_installed(&mut self) {
+        if let Some(name) = self.restored_installed.clone() {
+            if self.installed.items.iter().any(|line| package_name(line).as_deref() == Some(name.as_str())) {
+                self.select_installed(&name);
+                self.restored_installed = None;
+            }
+        }
+    }
+
+    fn dispatch(&mut self, event: Event) -> Vec<Task> {
+        match event {
+            Event::Input(TermEvent::Key(key)) => {
+                self.dirty = true;
+                self.handle_key(key)
+            }
+            Event::Input(TermEvent::Mouse(mouse)) => self.handle_mouse(mouse),
+            Event::Input(TermEvent::Resize(width, height)) => {
+                self.screen = Rect::new(0, 0, width, height);
+                self.dirty = true;
+                Vec::new()
+            }
+            Event::Quit => {
+                info!("exiting on a signal");
+                self.should_quit = true;
+                Vec::new()
+            }
+            Event::Suspend => vec![Task::Suspend],
+            Event::Tick => vec![Task::RefreshCurrentVersion],
+            Event::Log(line) => {
+                self.log_lines.push_back(line);
+                while self.log_lines.len() > LOG_HISTORY {
+                    self.log_lines.pop_front();
+                }
+                self.sync_log();
+                self.dirty |= self.show_log;
+                Vec::new()
+            }
+            Event::ToastTimeout => {
+                let now = Instant::now();
+                let before = self.toasts.len();
+                self.toasts.retain(|toast| toast.expires > now);
+                self.dirty |= self.toasts.len() != before;
+                Vec::new()
+            }
+            Event::InterpreterInspected { version, interpreter } => {
+                self.interpreters.insert(version, interpreter);
+                self.dirty = true;
+                Vec::new()
+            }
+            Event::FsChanged(FsChange::ActiveVersion) => vec![Task::RefreshCurrentVersion],
+            Event::FsChanged(FsChange::Versions) => vec![Task::RefreshVersions],
+            // The watcher saw the change happen, so the fingerprint isn't asked
+            Event::FsChanged(FsChange::SitePackages(version)) => self.request_packages(version, true).into_iter().collect(),
+            Event::VersionsChanged(Ok(versions)) => self.set_versions(versions),
+            Event::VersionsChanged(Err(e)) => {
+                self.report_error(format!("Listing Python versions failed: {}", e));
+                Vec::new()
+            }
+            Event::CurrentVersion(Ok(version)) => self.set_current_version(version),
+            Event::StatusRead { python_env, virtual_env } => {
+                self.python_env = python_env;
+                self.virtual_env = virtual_env;
+                self.dirty = true;
+                Vec::new()
+            }
+            Event::CurrentVersion(Err(e)) => {
+                self.report_error(format!("Reading the active version failed: {}", e));
+                Vec::new()
+            }
+            Event::PackagesRefreshing(version) => {
+                self.set_freshness(&version, Freshness::Refreshing);
+                Vec::new()
+            }
+            Event::PackagesUnchanged(version) => {
+                self.set_freshness(&version, Freshness::Fresh);
+                self.finish_refresh(version).into_iter().collect()
+            }
+            Event::PackagesLoaded { version, inventory } => {
+                self.package_errors.remove(&version);
+                self.package_cache.insert(version.clone(), inventory);
+                self.dirty = true;
+                self.finish_refresh(version).into_iter().collect()
+            }
+            Event::PackagesFailed { version, error } => {
+                self.set_freshness(&version, Freshness::Fresh);
+                self.report_error(format!("Reading packages for {} failed: {}", version, error));
+                self.package_errors.insert(version.clone(), error.to_string());
+                self.finish_refresh(version).into_iter().collect()
+            }
+            Event::SearchResults(result) => {
+                self.finish_job(Job::Search);
+                match result {
+                    Ok(packages) => {
+                        self.pypi_packages.set_items(packages);
+                        if let Some(package) = self.restored_package.take() {
+                            if let Some(index) = self.pypi_packages.items.iter().position(|p| *p == package) {
+                                self.pypi_packages.select(index);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        self.restored_package = None;
+                        self.report_error(format!("Searching PyPI failed: {}", e));
+                    }
+                }
+                Vec::new()
+            }
+            Event::AvailableVersions(Ok(versions)) => {
+                let filters = &self.config.filters;
+                self.available.set_items(versions.into_iter().filter(|v| filters.available_visible(v)).collect());
+                self.dirty = true;
+                Vec::new()
+            }
+            Event::AvailableVersions(Err(e)) => {
+                self.available_requested = false;
+                self.available_error = Some(e.to_string());
+                self.report_error(format!("Listing installable versions failed: {}", e));
+                Vec::new()
+            }
+            Event::InstallFinished { package, result } => {
+                self.finish_job(Job::Install);
+                self.show_pypi = false;
+                match result {
+                    Ok(()) => self.notify(Level::INFO, format!("Installed {}", package)),
+                    Err(e) => {
+                        self.report_error(format!("Installing {} failed: {}", package, e));
+                        Vec::new()
+                    }
+                }
+            }
+            Event::UninstallFinished { package, result } => {
+                self.finish_job(Job::Uninstall);
+                match result {
+                    Ok(()) => self.notify(Level::INFO, format!("Uninstalled {}", package)),
+                    Err(e) => {
+                        self.report_error(format!("Uninstalling {} failed: {}", package, e));
+                        Vec::new()
+                    }
+                }
+            }
+            Event::SwitchFinished { version, scope: Scope::Global, result: Ok(()) } => {
+                self.notify(Level::INFO, format!("Switched to {}", version))
+            }
+            Event::SwitchFinished { version, scope: Scope::Local, result: Ok(()) } => {
+                self.notify(Level::INFO, format!("Switched to {} in this directory", version))
+            }
+            Event::SwitchFinished { version, result: Err(e), .. } => {
+                self.report_error(format!("Switching to {} failed: {}", version, e));
+                Vec::new()
+            }
+            Event::VenvCreated { path, result } => {
+                self.finish_job(Job::CreateVenv);
+                match result {
+                    Ok(()) => self.notify(Level::INFO, format!("Created a virtual environment in {}", path.display())),
+                    Err(e) => {
+                        self.report_error(format!("Creating a virtual environment in {} failed: {}", path.display(), e));
+                        Vec::new()
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Vec<Task> {
+        if self.command_line.is_some() {
+            return self.handle_command_key(key);
+        }
+        if let Some(input) = self.searching {
+            return self.handle_search_key(input, key);
+        }
+        let action = self.keymap.action(key);
+        if let Some(modal) = self.modals.last_mut() {
+            // Only the control-key quit and suspend get past a dialog, so a stuck
+            // one can't trap the user
+            if let (true, Some(action @ (Action::Quit | Action::Suspend))) = (key.modifiers.contains(KeyModifiers::CONTROL), action) {
+                return self.handle_action(action);
+            }
+            let outcome = modal.handle_key(key, &self.keymap);
+            return self.resolve_modal(outcome);
+        }
+
+        // Alt and a digit jump to tabs, since plain digits are counts for the motions
+        if let (KeyCode::Char(digit @ '1'..='9'), false) = (key.code, self.show_help) {
+            if key.modifiers == KeyModifiers::ALT {
+                return self.select_version(digit as usize - '1' as usize);
+            }
+        }
+        match self.navigator.handle_key(key, &self.keymap) {
+            Step::Motion(motion) => return self.apply_motion(motion),
+            Step::Consumed => return Vec::new(),
+            Step::Other => {}
+        }
+        match action {
+            Some(action) => self.handle_action(action),
+            None => Vec::new(),
+        }
+    }
+
+    // What a bound key or a click on a popup line does
+    fn handle_action(&mut self, action: Action) -> Vec<Task> {
+        if self.show_help {
+            match action {
+                Action::Help | Action::Cancel => self.show_help = false,
+                Action::Quit => self.should_quit = true,
+                Action::Down => return self.move_selection(|list, _| list.down()),
+                Action::Up => return self.move_selection(|list, _| list.up()),
+                Action::PageDown => return self.move_selection(StatefulList::page_down),
+                Action::PageUp => return self.move_selection(StatefulList::page_up),
+                _ => {}
+            }
+            return Vec::new();
+        }
+        match action {
+            Action::Help => {
+                self.open_help();
+                Vec::new()
+            }
+            Action::Command => {
+                self.command_line = Some(CommandLine::default());
+                Vec::new()
+            }
+            Action::Cancel if self.error_message.is_some() => {
+                self.error_message = None;
+                Vec::new()
+            }
+            Action::Cancel if self.diff.is_some() => {
+                self.diff = None;
+                Vec::new()
+            }
+            Action::Find => {
+                let list = self.focused_list();
+                let origin = self.list(list).selected();
+                self.list_mut(list).search.query.clear();
+                self.searching = Some(SearchInput { list, origin });
+                Vec::new()
+            }
+            Action::NextMatch | Action::PreviousMatch => {
+                let list = self.focused_list();
+                let target = self.list_mut(list);
+                let from = target.selected();
+                let moved = target.select_match(from, action == Action::NextMatch, false);
+                self.list_moved(list, moved)
+            }
+            Action::ToggleFilter => {
+                let list = self.focused_list();
+                let before = self.list(list).selected();
+                self.list_mut(list).toggle_filter();
+                let moved = self.list(list).selected() != before;
+                self.list_moved(list, moved)
+            }
+            // A search is cleared before its pane is closed
+            Action::Cancel if !self.list(self.focused_list()).search.is_empty() => {
+                let list = self.focused_list();
+                self.list_mut(list).clear_search();
+                Vec::new()
+            }
+            Action::NextPane if !self.show_pypi && !self.show_available => {
+                self.packages_focused = !self.packages_focused;
+                Vec::new()
+            }
+            Action::Cancel if self.focused_list() == ListId::Available => {
+                self.show_available = false;
+                Vec::new()
+            }
+            Action::ToggleAvailable => self.toggle_available(),
+            Action::Cancel if self.show_log => {
+                self.show_log = false;
+                Vec::new()
+            }
+            Action::ToggleLog => {
+                self.show_log = !self.show_log;
+                Vec::new()
+            }
+            Action::CycleLogFilter if self.show_log => {
+                self.log_filter = match self.log_filter {
+                    Level::TRACE => Level::DEBUG,
+                    Level::DEBUG => Level::INFO,
+                    Level::INFO => Level::WARN,
+                    Level::WARN => Level::ERROR,
+                    _ => Level::TRACE,
+                };
+                self.sync_log();
+                Vec::new()
+            }
+            Action::Quit => {
+                info!("exiting");
+                self.should_quit = true;
+                Vec::new()
+            }
+            Action::Suspend => vec![Task::Suspend],
+            Action::Down => self.move_selection(|list, _| list.down()),
+            Action::Up => self.move_selection(|list, _| list.up()),
+            Action::PageDown => self.move_selection(StatefulList::page_down),
+            Action::PageUp => self.move_selection(StatefulList::page_up),
+            Action::Left => self.cycle_tab(false),
+            Action::Right => self.cycle_tab(true),
+            Action::MoveTabLeft => {
+                self.move_tab(false);
+                Vec::new()
+            }
+            Action::MoveTabRight => {
+                self.move_tab(true);
+                Vec::new()
+            }
+            Action::PinTab => {
+                self.toggle_pin();
+                Vec::new()
+            }
+            Action::GrowPane => self.resize_pane(true),
+            Action::ShrinkPane => self.resize_pane(false),
+            Action::ZoomPane => {
+                self.zoom = !self.zoom;
+                Vec::new()
+            }
+            Action::ToggleSplit => {
+                self.layout.split = match self.layout.split {
+                    Split::Horizontal => Split::Vertical,
+                    Split::Vertical => Split::Horizontal,
+                };
+                vec![Task::SaveLayout(self.layout)]
+            }
+            Action::Confirm if self.show_pypi => {
+                if let Some(package) = self.pypi_packages.selected_item() {
+                    self.modals.push(Modal::Confirm {
+                        title: String::from("Install"),
+                        message: format!("Install {} with the active Python's pip?", package),
+                        intent: Intent::Install(package.clone()),
+                    });
+                }
+                Vec::new()
+            }
+            Action::Confirm if self.packages_focused || self.show_available => Vec::new(),
+            Action::Confirm => {
+                if let Some(version) = self.selected_version().map(|v| clean_version(v)) {
+                    self.modals.push(Modal::Choice {
+                        title: format!("Options for {}", version),
+                        choices: vec![
+                            Choice {
+                                label: "Switch to this version",
+                                action: Some(Action::SwitchVersion),
+                                intent: Intent::Switch(version),
+                            },
+                            Choice {
+                                label: "Add packages to this version",
+                                action: Some(Action::AddPackages),
+                                intent: Intent::AddPackages,
+                            },
+                        ],
+                        selected: 0,
+                    });
+                }
+                Vec::new()
+            }
+            Action::Cancel if self.show_pypi => {
+                self.show_pypi = false;
+                Vec::new()
+            }
+            Action::Cancel if self.packages_focused => {
+                self.packages_focused = false;
+                Vec::new()
+            }
+            Action::Search if self.show_pypi => self.search_pypi(String::new()),
+            Action::EditSearch if self.show_pypi => {
+                self.modals.push(Modal::Input {
+                    title: String::from("Search PyPI"),
+                    prompt: Prompt::SearchPyPI,
+                    value: String::new(),
+                });
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // Close the dialog on top if it was answered, and do what it asked
+    fn resolve_modal(&mut self, outcome: Outcome) -> Vec<Task> {
+        let intent = match outcome {
+            Outcome::Open => return Vec::new(),
+            Outcome::Dismissed => {
+                self.modals.pop();
+                return Vec::new();
+            }
+            Outcome::Done(intent) => {
+                self.modals.pop();
+                intent
+            }
+        };
+        match intent {
+            Intent::Switch(version) => vec![Task::Switch { version, scope: Scope::Global }],
+            Intent::AddPackages => self.open_pypi(String::new()),
+            Intent::Install(package) => self.start_job(Job::Install, format!("Installing {}", package), Task::Install(package)),
+            Intent::Uninstall(package) => {
+                self.start_job(Job::Uninstall, format!("Uninstalling {}", package), Task::Uninstall(package))
+            }
+            Intent::SearchPyPI(query) => self.search_pypi(query),
+        }
+    }
+
+    // Show a progress box until the task's result comes back
+    fn start_job(&mut self, job: Job, message: String, task: Task) -> Vec<Task> {
+        let title = match job {
+            Job::Search => "Searching",
+            Job::Install => "Installing",
+            Job::Uninstall => "Uninstalling",
+            Job::CreateVenv => "Creating a virtual environment",
+        };
+        self.modals.push(Modal::Progress { title: title.to_string(), message, job });
+        vec![task]
+    }
+
+    // The progress box of a finished job, unless it was already hidden
+    fn finish_job(&mut self, job: Job) {
+        if let Some(index) = self.modals.iter().rposition(|modal| matches!(modal, Modal::Progress { job: j, .. } if *j == job)) {
+            self.modals.remove(index);
+        }
+        self.dirty = true;
+    }
+
+    // Open or close the available versions, reading them unless they were already
+    fn toggle_available(&mut self) -> Vec<Task> {
+        self.show_available = !self.show_available;
+        if !self.show_available || self.available_requested {
+            return Vec::new();
+        }
+        self.available_requested = true;
+        self.available_error = None;
+        vec![Task::ListAvailable]
+    }
+
+    fn search_pypi(&mut self, query: String) -> Vec<Task> {
+        self.pypi_query = query.clone();
+        let message = if query.is_empty() { String::from("Listing packages from PyPI") } else { format!("Searching PyPI for {}", query) };
+        self.start_job(Job::Search, message, Task::SearchPyPI(query))
+    }
+
+    // The keys of the focused pane, one per line with the keys lined up, from the top
+    fn open_help(&mut self) {
+        let hints = self.hints();
+        let width = hints.iter().map(|hint| hint.keys.chars().count()).max().unwrap_or(0);
+        self.help.set_items(hints.iter().map(|hint| format!("{:<width$}  {}", hint.keys, hint.description, width = width)).collect());
+        self.show_help = true;
+    }
+
+    // Move the cursor of the help overlay or the list in the panel if either is
+    // open, otherwise of the focused versions or packages; `motion` gets the number of
+    // rows the list shows, for paging
+    fn move_selection(&mut self, motion: impl FnOnce(&mut StatefulList<String>, usize) -> bool) -> Vec<Task> {
+        if self.show_help {
+            let height = ui::help_area(self.screen, &self.help).height.saturating_sub(2) as usize;
+            motion(&mut self.help, height);
+            return Vec::new();
+        }
+        let areas = self.areas();
+        let list = self.focused_list();
+        let area = match list {
+            ListId::Versions => areas.versions,
+            ListId::Installed => areas.packages,
+            ListId::PyPI | ListId::Available | ListId::Log => areas.panel,
+        };
+        let moved = motion(self.list_mut(list), area.height.saturating_sub(2) as usize);
+        self.list_moved(list, moved)
+    }
+
+    // A version under the cursor has its packages read
+    fn list_moved(&mut self, list: ListId, moved: bool) -> Vec<Task> {
+        if list != ListId::Versions || !moved {
+            return Vec::new();
+        }
+        self.versions.selected().and_then(|index| self.request_packages_at(index)).into_iter().collect()
+    }
+
+    // Keys while typing a query; the cursor follows the first match from where it
+    // was as the query is typed, and a filter narrows the rows with it
+    fn handle_search_key(&mut self, input: SearchInput, key: KeyEvent) -> Vec<Task> {
+        if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+            self.searching = None;
+        }
+        let list = self.list_mut(input.list);
+        match key.code {
+            KeyCode::Enter => return Vec::new(),
+            KeyCode::Esc => list.search.query.clear(),
+            KeyCode::Backspace => {
+                list.search.query.pop();
+            }
+            KeyCode::Char(c) => list.search.query.push(c),
+            _ => return Vec::new(),
+        }
+        let before = list.selected();
+        list.clamp();
+        if !list.select_match(input.origin, true, true) {
+            if let Some(origin) = input.origin {
+                list.select(origin);
+            }
+            list.clamp();
+        }
+        let moved = list.selected() != before;
+        self.list_moved(input.list, moved)
+    }
+
+    fn apply_motion(&mut self, motion: Motion) -> Vec<Task> {
+        let mut error = None;
+        let tasks = self.move_selection(|list, height| {
+            navigation::apply(list, motion, height).unwrap_or_else(|e| {
+                error = Some(e);
+                false
+            })
+        });
+        if let Some(e) = error {
+            self.report_error(format!("Jumping to a mark failed: {}", e));
+        }
+        tasks
+    }
+
+    // Five percent more or less for the focused pane: the version list's share of
+    // the main area, or the panel's height against the main area. Stops at the
+    // limits the config enforces.
+    fn resize_pane(&mut self, grow: bool) -> Vec<Task> {
+        const STEP: u16 = 5;
+        let pane = self.focused_pane();
+        let layout = &mut self.layout;
+        let resized = match (pane, grow) {
+            (Pane::Versions, true) if layout.versions + STEP <= 90 => {
+                layout.versions += STEP;
+                true
+            }
+            (Pane::Versions, false) if layout.versions >= 10 + STEP => {
+                layout.versions -= STEP;
+                true
+            }
+            (Pane::Panel, true) if layout.main >= 20 + STEP => {
+                layout.main -= STEP;
+                layout.panel += STEP;
+                true
+            }
+            (Pane::Panel, false) if layout.panel >= 2 * STEP => {
+                layout.panel -= STEP;
+                layout.main += STEP;
+                true
+            }
+            _ => false,
+        };
+        if resized {
+            vec![Task::SaveLayout(self.layout)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn select_version(&mut self, index: usize) -> Vec<Task> {
+        if index >= self.versions.len() || !self.versions.select(index) {
+            return Vec::new();
+        }
+        self.request_packages_at(index).into_iter().collect()
+    }
+
+    // The next or previous tab, wrapping around at either end
+    fn cycle_tab(&mut self, forward: bool) -> Vec<Task> {
+        let (len, selected) = match (self.versions.len(), self.versions.selected()) {
+            (0, _) | (_, None) => return Vec::new(),
+            (len, Some(selected)) => (len, selected),
+        };
+        self.select_version(if forward { (selected + 1) % len } else { (selected + len - 1) % len })
+    }
+
+    fn is_pinned(&self, version: &str) -> bool {
+        self.pinned.contains(&clean_version(version))
+    }
+
+    // Swap the selected tab with its neighbour; pinned tabs stay in front of the rest
+    fn move_tab(&mut self, forward: bool) {
+        let selected = match self.versions.selected() {
+            Some(selected) => selected,
+            None => return,
+        };
+        let other = match (forward, selected) {
+            (true, _) if selected + 1 < self.versions.len() => selected + 1,
+            (false, 1..) => selected - 1,
+            _ => return,
+        };
+        if self.is_pinned(&self.versions.items[selected]) != self.is_pinned(&self.versions.items[other]) {
+            return;
+        }
+        self.versions.items.swap(selected, other);
+        self.versions.select(other);
+    }
+
+    fn toggle_pin(&mut self) {
+        let version = match self.selected_version() {
+            Some(version) => clean_version(version),
+            None => return,
+        };
+        if !self.pinned.remove(&version) {
+            self.pinned.insert(version);
+        }
+        let versions = self.arrange(self.versions.items.clone());
+        self.set_tabs(versions);
+    }
+
+    // Put `versions` in tab order: pinned first, then the order the user left the
+    // tabs in, with versions not seen before at the end in discovery order
+    fn arrange(&self, mut versions: Vec<String>) -> Vec<String> {
+        let position = |version: &String| {
+            let name = clean_version(version);
+            let known = self.versions.items.iter().position(|v| clean_version(v) == name);
+            (!self.pinned.contains(&name), known.unwrap_or(usize::MAX))
+        };
+        versions.sort_by_key(position);
+        versions
+    }
+
+    // Replace the tabs, keeping the selection on the same version
+    fn set_tabs(&mut self, versions: Vec<String>) {
+        let selected = self.selected_version().map(|v| clean_version(v));
+        self.versions.set_items(versions);
+        if let Some(index) = selected.and_then(|selected| self.versions.items.iter().position(|v| clean_version(v) == selected)) {
+            self.versions.select(index);
+        }
+    }
+
+    fn open_pypi(&mut self, query: String) -> Vec<Task> {
+        if !self.config.backends.pypi {
+            self.report_error(String::from("Searching PyPI is disabled in the config (backends.pypi)"));
+            return Vec::new();
+        }
+        self.show_pypi = true;
+        self.search_pypi(query)
+    }
+
+    // Editing keys bypass the keymap, like those of the search query
+    fn handle_command_key(&mut self, key: KeyEvent) -> Vec<Task> {
+        let line = match self.command_line.as_mut() {
+            Some(line) => line,
+            None => return Vec::new(),
+        };
+        match key.code {
+            KeyCode::Enter => {
+                let input = self.command_line.take().map(|line| line.input).unwrap_or_default();
+                return self.run_command_line(input.trim());
+            }
+            KeyCode::Esc => self.command_line = None,
+            // Backspace on an empty line leaves it, like in vim
+            KeyCode::Backspace if line.input.is_empty() => self.command_line = None,
+            KeyCode::Backspace => {
+                line.input.pop();
+                line.completions.clear();
+            }
+            KeyCode::Tab => self.complete_command_line(),
+            KeyCode::Up => line.history_back(&self.history),
+            KeyCode::Down => line.history_forward(&self.history),
+            KeyCode::Char(c) => {
+                line.input.push(c);
+                line.completions.clear();
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    // Clicks select rows and pick dialog choices; the wheel moves the selection of
+    // the list under the pointer. Ignored while a command is being typed or the screen
+    // is too small to show the panes.
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Vec<Task> {
+        if self.command_line.is_some() || !ui::fits(self.screen) {
+            return Vec::new();
+        }
+        let areas = self.areas();
+        let (x, y) = (mouse.column, mouse.row);
+        if let Some(modal) = self.modals.last_mut() {
+            // Nothing behind a dialog can be clicked
+            let area = ui::modal_area(self.screen, modal, &self.keymap);
+            return match (mouse.kind, ui::row_in(area, x, y)) {
+                (MouseEventKind::Down(MouseButton::Left), Some(row)) => {
+                    self.dirty = true;
+                    let outcome = modal.click(row);
+                    self.resolve_modal(outcome)
+                }
+                _ => Vec::new(),
+            };
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if self.show_help => {
+                self.show_help = false;
+                self.dirty = true;
+                Vec::new()
+            }
+            MouseEventKind::Down(MouseButton::Left) if areas.tabs.height > 0 && y == areas.tabs.y => {
+                self.dirty = true;
+                match ui::tab_positions(self, areas.tabs.width).into_iter().find(|&(_, start, width)| x >= start && x < start + width) {
+                    Some((index, _, _)) => self.select_version(index),
+                    None => Vec::new(),
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let (list @ (ListId::PyPI | ListId::Available | ListId::Log), Some(row)) = (self.focused_list(), ui::row_in(areas.panel, x, y)) {
+                    return self.click_row(row, list);
+                }
+                if let Some(row) = ui::row_in(areas.packages, x, y) {
+                    return self.click_row(row, ListId::Installed);
+                }
+                match ui::row_in(areas.versions, x, y) {
+                    Some(row) => self.click_row(row, ListId::Versions),
+                    None => Vec::new(),
+                }
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp if self.show_help => {
+                self.click_action(if mouse.kind == MouseEventKind::ScrollDown { Action::Down } else { Action::Up })
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let down = mouse.kind == MouseEventKind::ScrollDown;
+                if matches!(self.focused_list(), ListId::PyPI | ListId::Available | ListId::Log) && ui::row_in(areas.panel, x, y).is_some() {
+                    return self.click_action(if down { Action::Down } else { Action::Up });
+                }
+                // The page's packages scroll without taking the keys from the versions
+                if ui::row_in(areas.packages, x, y).is_some() {
+                    self.dirty = true;
+                    if down { self.installed.down() } else { self.installed.up() };
+                    return Vec::new();
+                }
+                if ui::row_in(areas.versions, x, y).is_none() {
+                    return Vec::new();
+                }
+                self.dirty = true;
+                self.move_selection(|list, _| if down { list.down() } else { list.up() })
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn click_action(&mut self, action: Action) -> Vec<Task> {
+        self.dirty = true;
+        self.handle_action(action)
+    }
+
+    // Clicking a row selects it; clicking the selected row again acts like Enter.
+    // A click on the versions or the page's packages also gives that list the keys.
+    fn click_row(&mut self, row: usize, clicked: ListId) -> Vec<Task> {
+        let list = self.list(clicked);
+        let index = match list.item_at_row(row) {
+            Some(index) => index,
+            None => return Vec::new(),
+        };
+        let again = list.selected() == Some(index);
+        self.dirty = true;
+        match clicked {
+            ListId::Versions => self.packages_focused = false,
+            ListId::Installed => self.packages_focused = true,
+            ListId::PyPI | ListId::Available | ListId::Log => {}
+        }
+        if again {
+            return self.handle_action(Action::Confirm);
+        }
+        match clicked {
+            ListId::Versions => self.select_version(index),
+            list => {
+                self.list_mut(list).select(index);
+                Vec::new()
+            }
+        }
+    }
+
+    // Versions complete by name, packages from the selected version's list
+    fn complete_command_line(&mut self) {
+        let versions: Vec<String> = self.versions.items.iter().map(|v| clean_version(v)).collect();
+        let packages: Vec<String> = self
+            .selected_inventory()
+            .map(|inventory| inventory.packages.iter().filter_map(|p| p.split_whitespace().next()).map(String::from).collect())
+            .unwrap_or_default();
+        if let Some(line) = self.command_line.as_mut() {
+            let (input, completions) = command::complete(&line.input, &versions, &packages);
+            line.input = input;
+            line.completions = completions;
+        }
+    }
+
+    // Remember the line, then carry it out; mistakes are shown like any other error
+    fn run_command_line(&mut self, input: &str) -> Vec<Task> {
+        if input.is_empty() {
+            return Vec::new();
+        }
+        if self.history.last().map(String::as_str) != Some(input) {
+            self.history.push(input.to_string());
+            let excess = self.history.len().saturating_sub(HISTORY_LIMIT);
+            self.history.drain(..excess);
+        }
+        let mut tasks = vec![Task::SaveHistory(self.history.clone())];
+        match command::parse(input).and_then(|command| self.run_command(command)) {
+            Ok(more) => tasks.extend(more),
+            Err(message) => self.report_error(format!(":{}: {}", input, message)),
+        }
+        tasks
+    }
+
+    fn run_command(&mut self, command: Command) -> Result<Vec<Task>, String> {
+        info!(?command, "running command");
+        let tasks = match command {
+            Command::Switch { version, scope } => vec![Task::Switch { version: self.resolve_version(&version)?, scope }],
+            Command::Install(package) => self.start_job(Job::Install, format!("Installing {}", package), Task::Install(package)),
+            // Typed out in full, but still hard to take back
+            Command::Uninstall(package) => {
+                self.modals.push(Modal::Confirm {
+                    title: String::from("Uninstall"),
+                    message: format!("Uninstall {} from the active Python?", package),
+                    intent: Intent::Uninstall(package),
+                });
+                Vec::new()
+            }
+            Command::Diff(left, right) => {
+                let (left, right) = (self.resolve_version(&left)?, self.resolve_version(&right)?);
+                let inventory = |version: &String| {
+                    self.package_cache
+                        .get(version)
+                        .map(|inventory| inventory.packages.as_slice())
+                        .ok_or_else(|| format!("the packages of {} haven't been read yet", version))
+                };
+                let lines = packages::diff_packages(inventory(&left)?, inventory(&right)?);
+                self.diff = Some(PackageDiff { left, right, lines });
+                Vec::new()
+            }
+            Command::CreateVenv { path, version } => {
+                let version = match version {
+                    Some(version) => self.resolve_version(&version)?,
+                    None => self.selected_version().map(|v| clean_version(v)).ok_or("no version is selected")?,
+                };
+                let message = format!("Creating {} with {}", path.display(), version);
+                self.start_job(Job::CreateVenv, message, Task::CreateVenv { version, path })
+            }
+            Command::Search(query) => self.open_pypi(query),
+            Command::Refresh => {
+                let mut tasks = vec![Task::RefreshVersions, Task::RefreshCurrentVersion];
+                tasks.extend(self.refresh_all_packages());
+                tasks
+            }
+            Command::Available => self.toggle_available(),
+            Command::Log => {
+                self.show_log = !self.show_log;
+                Vec::new()
+            }
+            Command::Help => {
+                self.open_help();
+                Vec::new()
+            }
+            Command::ResetLayout => {
+                self.layout = self.config.layout;
+                self.zoom = false;
+                vec![Task::SaveLayout(self.layout)]
+            }
+            Command::Quit => {
+                info!("exiting");
+                self.should_quit = true;
+                Vec::new()
+            }
+        };
+        Ok(tasks)
+    }
+
+    // An installed version by its full name or a unique prefix, so "3.12" finds 3.12.4
+    fn resolve_version(&self, name: &str) -> Result<String, String> {
+        let versions: Vec<String> = self.versions.items.iter().map(|v| clean_version(v)).collect();
+        if versions.iter().any(|v| v == name) {
+            return Ok(name.to_string());
+        }
+        let matches: Vec<&String> = versions.iter().filter(|v| v.starts_with(name)).collect();
+        match matches.as_slice() {
+            [only] => Ok(only.to_string()),
+            [] => Err(format!("no installed version matches `{}`", name)),
+            _ => Err(format!(
+                "`{}` matches several versions: {}",
+                name,
+                matches.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    // Name of the pane or dialog that keys currently act on
+    pub fn focus_name(&self) -> &str {
+        if self.command_line.is_some() {
+            "Command line"
+        } else if let Some(modal) = self.modals.last() {
+            modal.title()
+        } else if self.show_pypi {
+            "PyPI packages"
+        } else if self.show_available {
+            "Available versions"
+        } else if self.show_log {
+            "Log"
+        } else if self.packages_focused {
+            "Installed packages"
+        } else {
+            "Python versions"
+        }
+    }
+
+    // What each key does in the focused pane, in the order `handle_key` tries them
+    pub fn hints(&self) -> Vec<Hint> {
+        // Typing a command or query takes every printable key, so the keymap doesn't apply
+        if self.command_line.is_some() {
+            return vec![
+                Hint { action: None, keys: String::from("enter"), description: "Run the command" },
+                Hint { action: None, keys: String::from("tab"), description: "Complete the word" },
+                Hint { action: None, keys: String::from("up/down"), description: "Step through the history" },
+                Hint { action: None, keys: String::from("esc"), description: "Cancel" },
+            ];
+        }
+        if self.searching.is_some() {
+            return vec![
+                Hint { action: None, keys: String::from("enter"), description: "Keep the search" },
+                Hint { action: None, keys: String::from("esc"), description: "Cancel the search" },
+                Hint { action: None, keys: String::from("backspace"), description: "Delete a character" },
+            ];
+        }
+        if let Some(modal) = self.modals.last() {
+            return modal.hints(&self.keymap);
+        }
+        let searched = !self.list(self.focused_list()).search.is_empty();
+        let mut actions: Vec<(Action, &'static str)> = Vec::new();
+        if self.error_message.is_some() {
+            actions.push((Action::Cancel, "Dismiss the error"));
+        }
+        if self.diff.is_some() {
+            actions.push((Action::Cancel, "Close the diff"));
+        }
+        if searched {
+            actions.push((Action::Cancel, "Clear the search"));
+        }
+        if self.show_pypi {
+            actions.push((Action::Down, "Next package"));
+            actions.push((Action::Up, "Previous package"));
+            actions.push((Action::Confirm, "Install the selected package"));
+            actions.push((Action::Search, "List packages"));
+            actions.push((Action::EditSearch, "Type a search query"));
+            actions.push((Action::Cancel, "Close the package list"));
+            actions.push((Action::PageDown, "Down a page of packages"));
+            actions.push((Action::PageUp, "Up a page of packages"));
+            actions.extend(MOTIONS.iter().map(|motion| (*motion, motion.description())));
+        } else if self.show_available {
+            actions.push((Action::Down, "Next version"));
+            actions.push((Action::Up, "Previous version"));
+            actions.push((Action::Cancel, "Close the available versions"));
+            actions.push((Action::PageDown, "Down a page of versions"));
+            actions.push((Action::PageUp, "Up a page of versions"));
+            actions.extend(MOTIONS.iter().map(|motion| (*motion, motion.description())));
+        } else if self.focused_list() == ListId::Log {
+            actions.push((Action::Down, "Next line"));
+            actions.push((Action::Up, "Previous line"));
+            actions.push((Action::PageDown, "Down a page of lines"));
+            actions.push((Action::PageUp, "Up a page of lines"));
+            actions.extend(MOTIONS.iter().map(|motion| (*motion, motion.description())));
+        } else if self.packages_focused {
+            actions.push((Action::Down, "Next package"));
+            actions.push((Action::Up, "Previous package"));
+            actions.push((Action::PageDown, "Down a page of packages"));
+            actions.push((Action::PageUp, "Up a page of packages"));
+            actions.extend(MOTIONS.iter().map(|motion| (*motion, motion.description())));
+            actions.push((Action::NextPane, "Back to the versions"));
+            actions.push((Action::Cancel, "Back to the versions"));
+            actions.push((Action::Left, Action::Left.description()));
+            actions.push((Action::Right, Action::Right.description()));
+        } else {
+            actions.push((Action::Down, "Next version"));
+            actions.push((Action::Up, "Previous version"));
+            actions.push((Action::Confirm, "Options for the selected version"));
+            actions.push((Action::PageDown, "Down a page of versions"));
+            actions.push((Action::PageUp, "Up a page of versions"));
+            actions.extend(MOTIONS.iter().map(|motion| (*motion, motion.description())));
+            actions.push((Action::NextPane, "Go to the version's packages"));
+            actions.push((Action::Left, Action::Left.description()));
+            actions.push((Action::Right, Action::Right.description()));
+            actions.push((Action::MoveTabLeft, Action::MoveTabLeft.description()));
+            actions.push((Action::MoveTabRight, Action::MoveTabRight.description()));
+            actions.push((Action::PinTab, Action::PinTab.description()));
+        }
+        actions.push((Action::Find, Action::Find.description()));
+        if searched {
+            actions.push((Action::NextMatch, Action::NextMatch.description()));
+            actions.push((Action::PreviousMatch, Action::PreviousMatch.description()));
+            actions.push((Action::ToggleFilter, Action::ToggleFilter.description()));
+        }
+        if self.show_available {
+            actions.push((Action::ToggleAvailable, "Hide the available versions"));
+        } else {
+            actions.push((Action::ToggleAvailable, "Show the versions pyenv can install"));
+        }
+        if self.show_log {
+            actions.push((Action::CycleLogFilter, "Change the log level filter"));
+            actions.push((Action::ToggleLog, "Hide the log"));
+        } else {
+            actions.push((Action::ToggleLog, "Show the log"));
+        }
+        actions.push((Action::GrowPane, Action::GrowPane.description()));
+        actions.push((Action::ShrinkPane, Action::ShrinkPane.description()));
+        actions.push((Action::ZoomPane, if self.zoom { "Show every pane again" } else { Action::ZoomPane.description() }));
+        actions.push((Action::ToggleSplit, Action::ToggleSplit.description()));
+        actions.push((Action::Help, Action::Help.description()));
+        actions.push((Action::Command, Action::Command.description()));
+        actions.push((Action::Suspend, Action::Suspend.description()));
+        actions.push((Action::Quit, Action::Quit.description()));
+
+        // An earlier entry wins when the same action appears twice (e.g. Cancel)
+        let mut seen = Vec::new();
+        let mut hints: Vec<Hint> = actions
+            .into_iter()
+            .filter(|(action, _)| {
+                let first = !seen.contains(action);
+                seen.push(*action);
+                first
+            })
+            .map(|(action, description)| Hint {
+                action: Some(action),
+                keys: if MOTIONS.contains(&action) {
+                    navigation::describe_keys(&self.keymap, action)
+                } else {
+                    self.keymap.describe_keys(action)
+                },
+                description,
+            })
+            .collect();
+        if let Some(jump) = hints.iter().position(|hint| hint.action == Some(Action::JumpToMark)) {
+            hints.insert(jump + 1, Hint { action: None, keys: String::from("1-9"), description: "Count for the next motion, as in 10j" });
+        }
+        if let Some(pin) = hints.iter().position(|hint| hint.action == Some(Action::PinTab)) {
+            hints.insert(pin + 1, Hint { action: None, keys: String::from("alt-1-9"), description: "Go to that tab" });
+        }
+        hints
+    }
+
+    // Follow a `pyenv global` change made by us or from outside the TUI
+    fn set_current_version(&mut self, version: String) -> Vec<Task> {
+        if version == self.current_python_version {
+            return Vec::new();
+        }
+        self.current_python_version = version;
+        self.dirty = true;
+        // Another interpreter may mean another pip and another environment
+        let mut tasks = vec![Task::RefreshStatus];
+        if let Some(index) = self
+            .versions
+            .items
+            .iter()
+            .position(|v| clean_version(v) == self.current_python_version)
+        {
+            self.versions.select(index);
+            tasks.extend(self.request_packages_at(index));
+        }
+        tasks
+    }
+
+    // Take a re-discovered version list, keeping the cursor on the same version
+    fn set_versions(&mut self, mut versions: Vec<String>) -> Vec<Task> {
+        versions.retain(|v| self.config.filters.installed_visible(v));
+        let versions = self.arrange(versions);
+        if versions == self.versions.items {
+            return Vec::new();
+        }
+        self.set_tabs(versions);
+        self.dirty = true;
+
+        let mut tasks = self.refresh_all_packages();
+        tasks.push(Task::WatchSitePackages(self.versions.items.clone()));
+        tasks.push(self.inspect_interpreters());
+        tasks
+    }
+
+    fn set_freshness(&mut self, version: &str, freshness: Freshness) {
+        if let Some(entry) = self.package_cache.get_mut(version) {
+            if entry.freshness != freshness {
+                entry.freshness = freshness;
+                self.dirty = true;
+            }
+        }
+    }
+
+    fn request_packages_at(&mut self, index: usize) -> Option<Task> {
+        let version = clean_version(self.versions.items.get(index)?);
+        self.request_packages(version, false)
+    }
+
+    // Ask for a version's packages; if a refresh is already running, ask again once
+    // it's done. Forced refreshes re-read even when the site-packages look unchanged.
+    fn request_packages(&mut self, version: String, force: bool) -> Option<Task> {
+        if !self.refreshing.insert(version.clone()) {
+            *self.requeued.entry(version).or_default() |= force;
+            return None;
+        }
+        let known = self.package_cache.get(&version).filter(|_| !force).map(|entry| Known {
+            site_packages_fingerprint: entry.site_packages_fingerprint,
+            freshness: entry.freshness,
+        });
+        Some(Task::RefreshPackages { version, known })
+    }
+
+    fn finish_refresh(&mut self, version: String) -> Option<Task> {
+        self.refreshing.remove(&version);
+        let force = self.requeued.remove(&version)?;
+        self.request_packages(version, force)
+    }
+}
+
+// The name out of a "name  version" row of the page's packages
+fn package_name(line: &str) -> Option<String> {
+    line.split_whitespace().next().map(String::from)
+}
@@ -0,0 +1,277 @@
+// The `:` command line, which reaches every operation by name instead of through
+// the popups: `:switch 3.12 --local`, `:install requests==2.32`, `:diff 3.11 3.12`
+// and so on. Tab completes the word being typed and the history is kept across
+// sessions in $XDG_STATE_HOME/py_manager/command_history.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use py_manager_core::operations::Scope;
+
+// Commands kept in the history, oldest dropped first
+pub const HISTORY_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Switch { version: String, scope: Scope },
+    Install(String),
+    Uninstall(String),
+    Diff(String, String),
+    // Uses the selected version when none is given
+    CreateVenv { path: PathBuf, version: Option<String> },
+    Search(String),
+    Refresh,
+    // Show or hide the versions pyenv can install
+    Available,
+    Log,
+    Help,
+    // Back to the `[layout]` of the config
+    ResetLayout,
+    Quit,
+}
+
+// Every command with its usage, in the order completion offers them
+pub const COMMANDS: [(&str, &str); 12] = [
+    ("switch", "switch <version> [--local]"),
+    ("install", "install <package>"),
+    ("uninstall", "uninstall <package>"),
+    ("diff", "diff <version> <version>"),
+    ("venv", "venv create <path> [version]"),
+    ("search", "search [query]"),
+    ("refresh", "refresh"),
+    ("available", "available"),
+    ("log", "log"),
+    ("help", "help"),
+    ("layout", "layout reset"),
+    ("quit", "quit"),
+];
+
+fn usage(name: &str) -> String {
+    let usage = COMMANDS.iter().find(|(command, _)| *command == name).map_or(name, |(_, usage)| usage);
+    format!("usage: {}", usage)
+}
+
+// Versions are taken as typed; the app resolves them against the installed ones
+pub fn parse(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (name, args) = match words.split_first() {
+        Some((name, args)) => (*name, args),
+        None => return Err(String::from("no command given")),
+    };
+    let command = match (name, args) {
+        ("switch", _) => {
+            let mut version = None;
+            let mut scope = Scope::Global;
+            for arg in args {
+                match *arg {
+                    "--local" => scope = Scope::Local,
+                    "--global" => scope = Scope::Global,
+                    flag if flag.starts_with('-') => return Err(format!("unknown option `{}`; {}", flag, usage(name))),
+                    arg if version.is_none() => version = Some(arg.to_string()),
+                    _ => return Err(usage(name)),
+                }
+            }
+            Command::Switch { version: version.ok_or_else(|| usage(name))?, scope }
+        }
+        ("install", [package]) => Command::Install(package.to_string()),
+        ("uninstall", [package]) => Command::Uninstall(package.to_string()),
+        ("diff", [left, right]) => Command::Diff(left.to_string(), right.to_string()),
+        ("venv", ["create", path]) => Command::CreateVenv { path: PathBuf::from(path), version: None },
+        ("venv", ["create", path, version]) => {
+            Command::CreateVenv { path: PathBuf::from(path), version: Some(version.to_string()) }
+        }
+        ("search", query) => Command::Search(query.join(" ")),
+        ("refresh", []) => Command::Refresh,
+        ("available", []) => Command::Available,
+        ("log", []) => Command::Log,
+        ("help", []) => Command::Help,
+        ("layout", ["reset"]) => Command::ResetLayout,
+        ("quit" | "q", []) => Command::Quit,
+        _ if COMMANDS.iter().any(|(command, _)| *command == name) => return Err(usage(name)),
+        _ => return Err(format!("unknown command `{}`", name)),
+    };
+    Ok(command)
+}
+
+// Everything the last word of `line` could be completed to
+pub fn candidates(line: &str, versions: &[String], packages: &[String]) -> Vec<String> {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    if line.is_empty() || line.ends_with(char::is_whitespace) {
+        words.push("");
+    }
+    let (current, before) = match words.split_last() {
+        Some(split) => split,
+        None => return Vec::new(),
+    };
+    let options: Vec<String> = match (before.first().copied(), before.len()) {
+        (None, _) => COMMANDS.iter().map(|(name, _)| name.to_string()).collect(),
+        (Some("switch"), _) => versions.iter().cloned().chain(["--local", "--global"].map(String::from)).collect(),
+        (Some("uninstall"), 1) => packages.to_vec(),
+        (Some("diff"), 1 | 2) => versions.to_vec(),
+        (Some("venv"), 1) => vec![String::from("create")],
+        (Some("layout"), 1) => vec![String::from("reset")],
+        (Some("venv"), 3) => versions.to_vec(),
+        _ => Vec::new(),
+    };
+    options.into_iter().filter(|option| option.starts_with(current)).collect()
+}
+
+// Fill in the last word of `line`: a single match is completed with a trailing
+// space, several only up to what they have in common and are returned for display
+pub fn complete(line: &str, versions: &[String], packages: &[String]) -> (String, Vec<String>) {
+    let matches = candidates(line, versions, packages);
+    let start = line.trim_end_matches(|c: char| !c.is_whitespace()).len();
+    match matches.as_slice() {
+        [] => (line.to_string(), Vec::new()),
+        [only] => (format!("{}{} ", &line[..start], only), Vec::new()),
+        _ => (format!("{}{}", &line[..start], common_prefix(&matches)), matches),
+    }
+}
+
+fn common_prefix(words: &[String]) -> &str {
+    let first = words[0].as_str();
+    let len = words[1..].iter().fold(first.len(), |len, word| {
+        first[..len]
+            .char_indices()
+            .zip(word.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(word.len()), |((i, _), _)| i)
+    });
+    &first[..len]
+}
+
+// What is being typed after the `:`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandLine {
+    pub input: String,
+    // Shown above the command line after a tab with several matches
+    pub completions: Vec<String>,
+    // Position while stepping back through the history; None for a new line
+    history_index: Option<usize>,
+}
+
+impl CommandLine {
+    // Replace the input with the previous (older) history entry
+    pub fn history_back(&mut self, history: &[String]) {
+        let index = match self.history_index {
+            Some(0) => return,
+            Some(index) => index - 1,
+            None if history.is_empty() => return,
+            None => history.len() - 1,
+        };
+        self.history_index = Some(index);
+        self.input = history[index].clone();
+        self.completions.clear();
+    }
+
+    // Step towards the newest entry, and past it back to an empty line
+    pub fn history_forward(&mut self, history: &[String]) {
+        let index = match self.history_index {
+            Some(index) => index + 1,
+            None => return,
+        };
+        self.history_index = (index < history.len()).then_some(index);
+        self.input = history.get(index).cloned().unwrap_or_default();
+        self.completions.clear();
+    }
+}
+
+// ~/.local/state/py_manager/command_history on Linux, the platform cache dir elsewhere
+pub fn history_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("py_manager").join("command_history"))
+}
+
+// One command per line, oldest first; no file yet means no history
+pub fn load_history(path: &Path) -> io::Result<Vec<String>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let lines: Vec<String> = text.lines().filter(|line| !line.trim().is_empty()).map(String::from).collect();
+    Ok(lines[lines.len().saturating_sub(HISTORY_LIMIT)..].to_vec())
+}
+
+pub fn save_history(path: &Path, history: &[String]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut text = history.join("\n");
+    text.push('\n');
+    fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions() -> Vec<String> {
+        vec!["system".to_string(), "3.11.7".to_string(), "3.12.4".to_string()]
+    }
+
+    #[test]
+    fn commands_parse_with_their_arguments() {
+        assert_eq!(
+            parse("switch 3.12.4 --local").unwrap(),
+            Command::Switch { version: "3.12.4".to_string(), scope: Scope::Local }
+        );
+        assert_eq!(parse("  install requests==2.32 ").unwrap(), Command::Install("requests==2.32".to_string()));
+        assert_eq!(parse("diff 3.11 3.12").unwrap(), Command::Diff("3.11".to_string(), "3.12".to_string()));
+        assert_eq!(
+            parse("venv create .venv").unwrap(),
+            Command::CreateVenv { path: PathBuf::from(".venv"), version: None }
+        );
+        assert_eq!(parse("search django rest").unwrap(), Command::Search("django rest".to_string()));
+        assert_eq!(parse("available").unwrap(), Command::Available);
+    }
+
+    #[test]
+    fn bad_commands_explain_themselves() {
+        assert_eq!(parse("switch").unwrap_err(), "usage: switch <version> [--local]");
+        assert_eq!(parse("switch 3.12 --user").unwrap_err(), "unknown option `--user`; usage: switch <version> [--local]");
+        assert_eq!(parse("venv 3.12").unwrap_err(), "usage: venv create <path> [version]");
+        assert_eq!(parse("frobnicate").unwrap_err(), "unknown command `frobnicate`");
+    }
+
+    #[test]
+    fn tab_completes_commands_then_arguments() {
+        let packages = vec!["requests".to_string(), "rich".to_string()];
+        assert_eq!(complete("sw", &versions(), &packages), (String::from("switch "), Vec::new()));
+        assert_eq!(complete("switch 3.1", &versions(), &packages).1, vec!["3.11.7", "3.12.4"]);
+        assert_eq!(complete("switch 3.12.4 --l", &versions(), &packages).0, "switch 3.12.4 --local ");
+        assert_eq!(complete("uninstall r", &versions(), &packages), (String::from("uninstall r"), packages.clone()));
+        assert_eq!(complete("uninstall ri", &versions(), &packages).0, "uninstall rich ");
+        assert_eq!(complete("u", &versions(), &packages).0, "uninstall ");
+        assert_eq!(complete("av", &versions(), &packages).0, "available ");
+        assert_eq!(complete("quit now", &versions(), &packages).0, "quit now");
+    }
+
+    #[test]
+    fn history_steps_back_and_forward() {
+        let history = vec!["log".to_string(), "switch 3.12.4".to_string()];
+        let mut line = CommandLine::default();
+        line.history_back(&history);
+        assert_eq!(line.input, "switch 3.12.4");
+        line.history_back(&history);
+        line.history_back(&history);
+        assert_eq!(line.input, "log");
+        line.history_forward(&history);
+        line.history_forward(&history);
+        assert_eq!(line.input, "");
+    }
+
+    #[test]
+    fn history_survives_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("command_history");
+        assert!(load_history(&path).unwrap().is_empty());
+        let history: Vec<String> = (0..HISTORY_LIMIT + 5).map(|i| format!("install pkg{}", i)).collect();
+        save_history(&path, &history).unwrap();
+        let loaded = load_history(&path).unwrap();
+        assert_eq!(loaded.len(), HISTORY_LIMIT);
+        assert_eq!(loaded.last().map(String::as_str), Some("install pkg204"));
+    }
+}
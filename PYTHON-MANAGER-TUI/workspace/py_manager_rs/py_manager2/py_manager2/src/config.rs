@@ -0,0 +1,465 @@
+// User settings read from $XDG_CONFIG_HOME/py_manager/config.toml. Every key is
+// optional and falls back to the built-in default; unknown keys are rejected so
+// typos don't silently do nothing. `[keys]` is read through the keymap crate, and
+// `[theme]` and `[themes]` through the theme crate.
+
+use keymap::{KeyTable, Keymap};
+use py_manager_core::packages::Sources;
+use serde::{Deserialize, Serialize};
+use theme::{Theme, ThemeChoice, ThemeTable};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub backends: Backends,
+    pub index: Index,
+    pub layout: Layout,
+    pub filters: Filters,
+    pub cache: Cache,
+    pub timing: Timing,
+    pub mouse: Mouse,
+    pub theme: ThemeChoice,
+    // Themes of your own, by name
+    #[serde(skip_serializing_if = "ThemeTable::is_empty")]
+    pub themes: ThemeTable,
+    // Action name -> keys, replacing that action's default keys
+    #[serde(skip_serializing_if = "KeyTable::is_empty")]
+    pub keys: KeyTable,
+}
+
+// Where package lists and search results may come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Backends {
+    // Read installed distributions straight from site-packages
+    pub site_packages: bool,
+    // Ask `pip list` when site-packages can't be read
+    pub pip: bool,
+    // Search the package index
+    pub pypi: bool,
+    // Watch pyenv's files for outside changes instead of polling
+    pub watch: bool,
+}
+
+impl Default for Backends {
+    fn default() -> Backends {
+        Backends { site_packages: true, pip: true, pypi: true, watch: true }
+    }
+}
+
+impl Backends {
+    // The part that decides where package lists are read from
+    pub fn sources(&self) -> Sources {
+        Sources { site_packages: self.site_packages, pip: self.pip }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Index {
+    // Base URL of a PyPI-compatible index; searches go to <url>/search/
+    pub url: String,
+}
+
+impl Default for Index {
+    fn default() -> Index {
+        Index { url: String::from("https://pypi.org") }
+    }
+}
+
+// Screen split, in percent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    // Heights of the version/package lists, the middle panel and the status area
+    pub main: u16,
+    pub panel: u16,
+    pub status: u16,
+    // Share of the main area taken by the version list
+    pub versions: u16,
+    pub split: Split,
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout { main: 70, panel: 20, status: 10, versions: 50, split: Split::Horizontal }
+    }
+}
+
+// How the version list and the selected version's page share the main area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Split {
+    // Side by side
+    Horizontal,
+    // One above the other
+    Vertical,
+}
+
+impl Layout {
+    // ~/.local/state/py_manager/layout.toml on Linux: the layout as it was last
+    // left in the TUI, which takes over from `[layout]` in the config
+    pub fn saved_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("py_manager").join("layout.toml"))
+    }
+
+    // None when nothing was saved yet; checked like `[layout]` in the config
+    pub fn load_saved(path: &Path) -> Result<Option<Layout>, ConfigError> {
+        let error = |message: String| ConfigError { path: Some(path.to_path_buf()), message };
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(error(e.to_string())),
+        };
+        let layout: Layout = toml::from_str(&text).map_err(|e| error(e.to_string().trim_end().to_string()))?;
+        Config { layout, ..Config::default() }.validate().map_err(|e| error(e.message))?;
+        Ok(Some(layout))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Filters {
+    // Installed versions containing any of these are not listed
+    pub hide_installed: Vec<String>,
+    // Installable versions containing any of these are left out of the available
+    // versions pane
+    pub hide_available: Vec<String>,
+}
+
+impl Default for Filters {
+    fn default() -> Filters {
+        Filters { hide_installed: Vec::new(), hide_available: vec![String::from("Anaconda")] }
+    }
+}
+
+impl Filters {
+    pub fn installed_visible(&self, version: &str) -> bool {
+        !self.hide_installed.iter().any(|hidden| version.contains(hidden.as_str()))
+    }
+
+    pub fn available_visible(&self, version: &str) -> bool {
+        !self.hide_available.iter().any(|hidden| version.contains(hidden.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Cache {
+    // Keep package lists on disk between sessions
+    pub enabled: bool,
+    // Directory for the package inventory cache; the platform cache dir when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for Cache {
+    fn default() -> Cache {
+        Cache { enabled: true, dir: None }
+    }
+}
+
+impl Cache {
+    // ~/.cache/py_manager/inventory.sqlite3 on Linux unless moved; None when disabled
+    pub fn inventory_path(&self) -> Option<PathBuf> {
+        if !self.enabled {
+            return None;
+        }
+        let dir = self.dir.clone().or_else(|| dirs::cache_dir().map(|dir| dir.join("py_manager")))?;
+        Some(dir.join("inventory.sqlite3"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Timing {
+    // How often to re-read the active version when file watching is unavailable
+    pub version_poll_ms: u64,
+    // How long a toast stays on screen
+    pub toast_ms: u64,
+}
+
+impl Default for Timing {
+    fn default() -> Timing {
+        Timing { version_poll_ms: 2000, toast_ms: 4000 }
+    }
+}
+
+impl Timing {
+    pub fn version_poll_interval(&self) -> Duration {
+        Duration::from_millis(self.version_poll_ms)
+    }
+
+    pub fn toast_duration(&self) -> Duration {
+        Duration::from_millis(self.toast_ms)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Mouse {
+    // Take over clicks and the wheel; off leaves the terminal's own text selection working
+    pub capture: bool,
+}
+
+impl Default for Mouse {
+    fn default() -> Mouse {
+        Mouse { capture: true }
+    }
+}
+
+// Why the config couldn't be used; `path` is None for a config that didn't come from a file
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "invalid config {}: {}", path.display(), self.message),
+            None => write!(f, "invalid config: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    // ~/.config/py_manager/config.toml on Linux, the platform config dir elsewhere
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("py_manager").join("config.toml"))
+    }
+
+    // Read `path`, or the default location when None. A missing default file just
+    // means built-in defaults; a missing file that was asked for is an error.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match Config::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => return Ok(Config::default()),
+            Err(e) => return Err(ConfigError { path: Some(path), message: e.to_string() }),
+        };
+        Config::parse(&text).map_err(|e| ConfigError { path: Some(path), ..e })
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let config: Config = toml::from_str(text).map_err(|e| ConfigError {
+            path: None,
+            message: e.to_string().trim_end().to_string(),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let fail = |message: String| Err(ConfigError { path: None, message });
+
+        if !self.backends.site_packages && !self.backends.pip {
+            return fail(String::from(
+                "backends.site_packages and backends.pip are both disabled, so no package list could be read",
+            ));
+        }
+        if !(self.index.url.starts_with("http://") || self.index.url.starts_with("https://")) {
+            return fail(format!("index.url must start with http:// or https:// (got {:?})", self.index.url));
+        }
+
+        let layout = self.layout;
+        let total = u32::from(layout.main) + u32::from(layout.panel) + u32::from(layout.status);
+        if total != 100 {
+            return fail(format!(
+                "layout.main + layout.panel + layout.status must add up to 100 (got {} + {} + {} = {})",
+                layout.main, layout.panel, layout.status, total
+            ));
+        }
+        if layout.main < 20 {
+            return fail(format!("layout.main must be at least 20 (got {})", layout.main));
+        }
+        if !(10..=90).contains(&layout.versions) {
+            return fail(format!("layout.versions must be between 10 and 90 (got {})", layout.versions));
+        }
+
+        if let Some(dir) = &self.cache.dir {
+            if !dir.is_absolute() {
+                return fail(format!("cache.dir must be an absolute path (got {:?})", dir.display().to_string()));
+            }
+        }
+        if self.timing.version_poll_ms < 100 {
+            return fail(format!("timing.version_poll_ms must be at least 100 (got {})", self.timing.version_poll_ms));
+        }
+        if self.timing.toast_ms == 0 {
+            return fail(String::from("timing.toast_ms must be greater than 0"));
+        }
+        if let Err(e) = Keymap::from_table(&self.keys) {
+            return fail(e.to_string());
+        }
+        if let Err(e) = Theme::validate(&self.theme, &self.themes) {
+            return fail(e.to_string());
+        }
+        Ok(())
+    }
+
+    // Validated like the keymap; NO_COLOR is up to the caller
+    pub fn theme(&self) -> Theme {
+        Theme::resolve(&self.theme, &self.themes).unwrap_or_default()
+    }
+
+    // Validated when the config was loaded, so this only falls back for a
+    // hand-built `Config`
+    pub fn keymap(&self) -> Keymap {
+        Keymap::from_table(&self.keys).unwrap_or_default()
+    }
+
+    // The config as TOML, with defaults filled in and the cache location and key
+    // bindings resolved
+    pub fn effective_toml(&self) -> String {
+        let mut effective = self.clone();
+        effective.keys = self.keymap().to_table();
+        if effective.cache.enabled && effective.cache.dir.is_none() {
+            effective.cache.dir = effective.cache.inventory_path().and_then(|path| path.parent().map(Path::to_path_buf));
+        }
+        toml::to_string_pretty(&effective).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Config::parse(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn an_empty_file_is_all_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn keys_override_only_what_they_name() {
+        let config = Config::parse("[layout]\nmain = 60\npanel = 30\n\n[backends]\npypi = false\n").unwrap();
+        assert_eq!(config.layout, Layout { main: 60, panel: 30, status: 10, versions: 50, split: Split::Horizontal });
+        assert!(!config.backends.pypi);
+        assert!(config.backends.pip);
+        assert_eq!(config.index, Index::default());
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected_with_their_location() {
+        let message = error("[layout]\nmian = 60\n");
+        assert!(message.contains("line 2"), "{}", message);
+        assert!(message.contains("unknown field `mian`"), "{}", message);
+    }
+
+    #[test]
+    fn layout_must_add_up_to_100() {
+        assert_eq!(
+            error("[layout]\nmain = 70\npanel = 20\nstatus = 20\n"),
+            "invalid config: layout.main + layout.panel + layout.status must add up to 100 (got 70 + 20 + 20 = 110)"
+        );
+    }
+
+    #[test]
+    fn a_saved_layout_is_read_back_and_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("layout.toml");
+        assert_eq!(Layout::load_saved(&path).unwrap(), None);
+
+        let layout = Layout { main: 50, panel: 40, status: 10, versions: 30, split: Split::Vertical };
+        layout.save(&path).unwrap();
+        assert_eq!(Layout::load_saved(&path).unwrap(), Some(layout));
+
+        fs::write(&path, "main = 90\npanel = 20\n").unwrap();
+        let message = Layout::load_saved(&path).unwrap_err().to_string();
+        assert!(message.contains("must add up to 100"), "{}", message);
+    }
+
+    #[test]
+    fn some_package_source_must_stay_enabled() {
+        assert!(error("[backends]\nsite_packages = false\npip = false\n").contains("both disabled"));
+    }
+
+    #[test]
+    fn index_url_must_be_http() {
+        assert!(error("[index]\nurl = \"pypi.org\"\n").contains("index.url must start with http"));
+    }
+
+    #[test]
+    fn relative_cache_dir_is_rejected() {
+        assert!(error("[cache]\ndir = \"cache\"\n").contains("cache.dir must be an absolute path"));
+    }
+
+    #[test]
+    fn errors_name_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[timing]\ntoast_ms = 0\n").unwrap();
+        let message = Config::load(Some(&path)).unwrap_err().to_string();
+        assert_eq!(message, format!("invalid config {}: timing.toast_ms must be greater than 0", path.display()));
+    }
+
+    #[test]
+    fn a_missing_explicit_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Config::load(Some(&dir.path().join("nope.toml"))).is_err());
+    }
+
+    #[test]
+    fn effective_config_round_trips() {
+        let config = Config::parse("[cache]\ndir = \"/tmp/py_manager\"\n\n[filters]\nhide_installed = [\"system\"]\n").unwrap();
+        let mut effective = Config::parse(&config.effective_toml()).unwrap();
+        assert_eq!(effective.keymap(), config.keymap());
+        effective.keys.clear();
+        assert_eq!(effective, config);
+    }
+
+    #[test]
+    fn conflicting_keys_are_rejected() {
+        assert_eq!(
+            error("[keys]\nsearch = [\"j\"]\n"),
+            "invalid config: key `j` is bound to both `down` and `search`"
+        );
+    }
+
+    #[test]
+    fn themes_are_picked_by_name_and_checked() {
+        let config = Config::parse("[theme]\nname = \"mine\"\n\n[themes.mine]\nbase = \"light\"\naccent = \"#005f87\"\n").unwrap();
+        assert_eq!(config.theme().name, "mine");
+        assert_eq!(
+            error("[themes.mine]\naccent = \"teal\"\n"),
+            "invalid config: themes.mine.accent: invalid color `teal`; use a name like \"cyan\", \"#rrggbb\" or 0-255"
+        );
+        assert!(error("[theme]\nname = \"solarized\"\n").contains("unknown theme `solarized`"));
+    }
+
+    #[test]
+    fn hidden_installed_versions_match_by_substring() {
+        let filters = Filters { hide_installed: vec![String::from("miniconda")], ..Filters::default() };
+        assert!(!filters.installed_visible("miniconda3-latest"));
+        assert!(filters.installed_visible("3.12.4"));
+        assert!(!Filters::default().available_visible("Anaconda3-2024.02"));
+        assert!(Filters::default().available_visible("3.13.0"));
+    }
+}
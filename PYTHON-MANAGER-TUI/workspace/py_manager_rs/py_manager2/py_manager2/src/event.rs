@@ -0,0 +1,68 @@
+use crossterm::event::{Event as TermEvent, EventStream};
+use futures::StreamExt;
+use py_manager_core::cache::Inventory;
+use py_manager_core::error::Error;
+use py_manager_core::operations::Scope;
+use py_manager_core::site_packages::Interpreter;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::logging::LogLine;
+use crate::watch::FsChange;
+
+// Everything that can change the app's state arrives as one of these
+#[derive(Debug)]
+pub enum Event {
+    Input(TermEvent),
+    Tick,
+    // A log line for the log panel
+    Log(LogLine),
+    // A toast may have reached its expiry time
+    ToastTimeout,
+    // SIGINT, SIGTERM, or a panic off the main thread: save the session and exit
+    Quit,
+    // SIGTSTP sent from outside, handled like Ctrl-Z
+    Suspend,
+    FsChanged(FsChange),
+    InterpreterInspected { version: String, interpreter: Interpreter },
+    VersionsChanged(Result<Vec<String>, Error>),
+    CurrentVersion(Result<String, Error>),
+    // What the status box shows about the environment; "Unknown" when unreadable
+    StatusRead { python_env: String, virtual_env: String },
+    PackagesRefreshing(String),
+    PackagesUnchanged(String),
+    PackagesLoaded { version: String, inventory: Inventory },
+    PackagesFailed { version: String, error: Error },
+    SearchResults(Result<Vec<String>, Error>),
+    // What `pyenv install --list` offers, newest first
+    AvailableVersions(Result<Vec<String>, Error>),
+    InstallFinished { package: String, result: Result<(), Error> },
+    UninstallFinished { package: String, result: Result<(), Error> },
+    SwitchFinished { version: String, scope: Scope, result: Result<(), Error> },
+    VenvCreated { path: PathBuf, result: Result<(), Error> },
+}
+
+// Forward terminal input to the event channel until the receiver goes away
+pub fn spawn_input(tx: UnboundedSender<Event>) {
+    tokio::spawn(async move {
+        let mut reader = EventStream::new();
+        while let Some(Ok(event)) = reader.next().await {
+            if tx.send(Event::Input(event)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+pub fn spawn_ticker(tx: UnboundedSender<Event>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            if tx.send(Event::Tick).is_err() {
+                break;
+            }
+        }
+    });
+}
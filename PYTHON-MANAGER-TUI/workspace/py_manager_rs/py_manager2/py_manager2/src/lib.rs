@@ -0,0 +1,14 @@
+// The pieces of py_manager2 behind the binary, split out so tests can drive them
+// without a terminal or a real pyenv.
+
+pub mod app;
+pub mod command;
+pub mod config;
+pub mod event;
+pub mod list;
+pub mod logging;
+pub mod modal;
+pub mod packages;
+pub mod session;
+pub mod ui;
+pub mod watch;
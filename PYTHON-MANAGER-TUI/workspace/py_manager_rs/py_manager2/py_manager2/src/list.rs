@@ -0,0 +1,307 @@
+// A scrollable list with a cursor and a `/` search. The cursor is None exactly
+// when no row is shown (an empty PyPI result, no versions found, a filter that
+// matches nothing), so moving around then is a no-op instead of an underflow.
+//
+// The scroll offset lives in a Cell because the window is only known once the
+// list is drawn, and drawing takes the app by shared reference. Mouse clicks read
+// it back to find the item under the pointer.
+//
+// Rows are what's on screen: every item, or only the matches while the search
+// filters. The cursor, the scrolling and the vim motions of the navigation crate
+// (through `Rows`) count rows; `selected` and `select` name items. The marks and
+// the search are kept across `set_items`, since they find their rows by text.
+
+use navigation::{Marks, Rows};
+use search::Search;
+use std::cell::Cell;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatefulList<T> {
+    pub items: Vec<T>,
+    selected: Option<usize>,
+    // First row on screen as of the last frame
+    offset: Cell<usize>,
+    marks: Marks,
+    pub search: Search,
+}
+
+impl<T: AsRef<str>> StatefulList<T> {
+    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
+        let selected = (!items.is_empty()).then_some(0);
+        StatefulList { items, selected, offset: Cell::new(0), marks: Marks::default(), search: Search::default() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> Option<&T> {
+        self.items.get(self.selected?)
+    }
+
+    // The items on screen, in order: the matches while filtering, otherwise all
+    pub fn shown(&self) -> Vec<usize> {
+        if self.search.filtering() {
+            self.matches()
+        } else {
+            (0..self.items.len()).collect()
+        }
+    }
+
+    // The items matching the search, in order; none without a query
+    pub fn matches(&self) -> Vec<usize> {
+        self.search.matches(self.items.iter().map(AsRef::as_ref))
+    }
+
+    // Move the cursor to item `index`, clamped to the last item. False when it didn't move.
+    pub fn select(&mut self, index: usize) -> bool {
+        let index = match self.items.len() {
+            0 => return false,
+            len => index.min(len - 1),
+        };
+        let moved = self.selected != Some(index);
+        self.selected = Some(index);
+        moved
+    }
+
+    // Move the cursor to row `row` of those shown, clamped to the last one
+    fn select_shown(&mut self, row: usize) -> bool {
+        let shown = self.shown();
+        match shown.get(row.min(shown.len().saturating_sub(1))) {
+            Some(&index) => self.select(index),
+            None => false,
+        }
+    }
+
+    fn cursor_row(&self) -> Option<usize> {
+        let selected = self.selected?;
+        self.shown().iter().position(|&i| i == selected)
+    }
+
+    // Stops at either end rather than wrapping
+    pub fn down(&mut self) -> bool {
+        self.select_shown(self.cursor_row().map_or(0, |i| i + 1))
+    }
+
+    pub fn up(&mut self) -> bool {
+        self.select_shown(self.cursor_row().map_or(0, |i| i.saturating_sub(1)))
+    }
+
+    pub fn page_down(&mut self, page: usize) -> bool {
+        self.select_shown(self.cursor_row().map_or(0, |i| i + page.max(1)))
+    }
+
+    pub fn page_up(&mut self, page: usize) -> bool {
+        self.select_shown(self.cursor_row().map_or(0, |i| i.saturating_sub(page.max(1))))
+    }
+
+    // New items with the cursor back on the first row
+    pub fn set_items(&mut self, items: Vec<T>) {
+        self.items = items;
+        self.selected = self.shown().first().copied();
+        self.offset.set(0);
+    }
+
+    // Put the cursor back on a shown row after `items` or the search was changed
+    // in place, staying on its item if that is still shown
+    pub fn clamp(&mut self) {
+        let shown = self.shown();
+        self.selected = match self.selected.map(|i| i.min(self.items.len().saturating_sub(1))) {
+            Some(i) if shown.contains(&i) => Some(i),
+            Some(i) => shown.iter().find(|&&s| s >= i).or_else(|| shown.last()).copied(),
+            None => shown.first().copied(),
+        };
+    }
+
+    // Go to the next or previous match from the cursor, wrapping around; the
+    // cursor's own row counts when `inclusive`. False when nothing matches.
+    pub fn select_match(&mut self, from: Option<usize>, forward: bool, inclusive: bool) -> bool {
+        match search::next_match(&self.matches(), from, forward, inclusive) {
+            Some(index) => {
+                self.select(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Hide or show the rows that don't match, keeping the cursor on a shown row
+    pub fn toggle_filter(&mut self) {
+        self.search.filter = !self.search.filter;
+        self.clamp();
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search.clear();
+        self.clamp();
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset.get()
+    }
+
+    // Scroll so the cursor is inside a window of `height` rows and return the new
+    // first row; called when the list is drawn
+    pub fn scroll(&self, height: usize) -> usize {
+        let offset = scroll_offset(self.offset.get(), self.cursor_row(), self.shown().len(), height);
+        self.offset.set(offset);
+        offset
+    }
+
+    // The item drawn on `row` (0 is the first row inside the border), if any
+    pub fn item_at_row(&self, row: usize) -> Option<usize> {
+        self.shown().get(self.offset.get() + row).copied()
+    }
+}
+
+impl<T: AsRef<str>> Rows for StatefulList<T> {
+    fn row_count(&self) -> usize {
+        self.shown().len()
+    }
+
+    fn selected_row(&self) -> Option<usize> {
+        self.cursor_row()
+    }
+
+    fn select_row(&mut self, row: usize) {
+        self.select_shown(row);
+    }
+
+    fn first_visible_row(&self) -> usize {
+        self.offset.get()
+    }
+
+    fn row_text(&self, row: usize) -> &str {
+        self.items[self.shown()[row]].as_ref()
+    }
+
+    fn marks(&mut self) -> &mut Marks {
+        &mut self.marks
+    }
+}
+
+// Where a list of `len` rows starts once `selected` is scrolled into a window of
+// `height` rows, moving as little as possible from `offset` and never leaving
+// empty rows at the bottom
+pub fn scroll_offset(offset: usize, selected: Option<usize>, len: usize, height: usize) -> usize {
+    if len == 0 || height == 0 {
+        return 0;
+    }
+    let selected = selected.unwrap_or(0).min(len - 1);
+    let mut offset = offset.min(len.saturating_sub(height));
+    if selected >= offset + height {
+        offset = selected + 1 - height;
+    }
+    offset.min(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(n: usize) -> StatefulList<String> {
+        StatefulList::with_items((0..n).map(|i| i.to_string()).collect())
+    }
+
+    #[test]
+    fn an_empty_list_has_no_cursor_to_move() {
+        let mut list = numbers(0);
+        assert!(!list.down());
+        assert!(!list.page_up(10));
+        assert_eq!(list.selected(), None);
+        assert_eq!(list.selected_item(), None);
+        assert_eq!(list.scroll(5), 0);
+        assert_eq!(list.item_at_row(0), None);
+    }
+
+    #[test]
+    fn the_cursor_stops_at_the_ends_and_moves_by_pages() {
+        let mut list = numbers(25);
+        assert!(!list.up());
+        assert!(list.page_down(10));
+        assert_eq!(list.selected(), Some(10));
+        list.page_down(10);
+        list.page_down(10);
+        assert_eq!(list.selected(), Some(24));
+        assert!(!list.down());
+        list.page_up(10);
+        assert_eq!(list.selected(), Some(14));
+    }
+
+    #[test]
+    fn the_window_follows_the_cursor() {
+        let mut list = numbers(25);
+        list.select(12);
+        assert_eq!(list.scroll(10), 3);
+        assert_eq!(list.item_at_row(0), Some(3));
+        // Moving within the window leaves it where it is
+        list.select(5);
+        assert_eq!(list.scroll(10), 3);
+        list.select(0);
+        assert_eq!(list.scroll(10), 0);
+        // A taller window never leaves rows empty at the bottom
+        list.select(24);
+        list.scroll(10);
+        assert_eq!(list.scroll(20), 5);
+    }
+
+    #[test]
+    fn filtering_in_place_keeps_the_cursor_in_range() {
+        let mut list = numbers(5);
+        list.select(4);
+        list.items.retain(|i| i.as_str() < "2");
+        list.clamp();
+        assert_eq!(list.selected(), Some(1));
+        list.items.clear();
+        list.clamp();
+        assert_eq!(list.selected(), None);
+    }
+
+    #[test]
+    fn a_filter_shows_only_the_matches_and_moves_between_them() {
+        let mut list = numbers(25);
+        list.select(3);
+        list.search.query = String::from("1");
+        // 1, 10..=19 and 21
+        assert_eq!(list.matches().len(), 12);
+        list.toggle_filter();
+        assert_eq!(list.shown().len(), 12);
+        // The cursor left its hidden row for the next shown one
+        assert_eq!(list.selected(), Some(10));
+        assert!(list.down());
+        assert_eq!(list.selected(), Some(11));
+        assert_eq!(list.item_at_row(0), Some(1));
+        list.page_down(20);
+        assert_eq!(list.selected(), Some(21));
+
+        list.search.query = String::from("nothing");
+        list.clamp();
+        assert_eq!(list.selected(), None);
+        list.clear_search();
+        assert_eq!(list.selected(), Some(0));
+        assert_eq!(list.shown().len(), 25);
+    }
+
+    #[test]
+    fn matches_are_found_from_the_cursor_either_way() {
+        let mut list = numbers(25);
+        list.search.query = String::from("2");
+        assert!(list.select_match(Some(3), true, false));
+        assert_eq!(list.selected(), Some(12));
+        assert!(list.select_match(Some(12), true, true));
+        assert_eq!(list.selected(), Some(12));
+        assert!(list.select_match(Some(2), false, false));
+        assert_eq!(list.selected(), Some(24));
+        list.search.query = String::from("x");
+        assert!(!list.select_match(Some(2), true, true));
+        assert_eq!(list.selected(), Some(24));
+    }
+}
@@ -0,0 +1,223 @@
+// Leveled, timestamped logging into a per-user directory. The log file is rotated
+// by size so a long session can't grow it without bound, and every line is also
+// forwarded to the app for its log panel.
+
+use std::fmt::{self, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::field::{Field, Visit};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::event::Event;
+
+// Rotate once the current file would grow past this
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+// Rotated files kept next to the current one: py_manager2.log.1 .. .N
+pub const KEPT_LOGS: usize = 3;
+
+// ~/.local/state/py_manager/logs on Linux, the platform cache dir elsewhere
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("py_manager").join("logs"))
+}
+
+// Install the global subscriber and return the file it writes to. Log lines reach
+// the app through `tx` even when the file can't be opened.
+pub fn init(level: LevelFilter, tx: UnboundedSender<Event>) -> io::Result<PathBuf> {
+    let (path, file_layer) = match open_log_file() {
+        Ok((path, file)) => {
+            let layer = tracing_subscriber::fmt::layer().with_ansi(false).with_writer(Mutex::new(file));
+            (Ok(path), Some(layer))
+        }
+        Err(e) => (Err(e), None),
+    };
+
+    tracing_subscriber::registry()
+        .with(level)
+        .with(file_layer)
+        .with(PanelLayer { tx })
+        .init();
+    path
+}
+
+fn open_log_file() -> io::Result<(PathBuf, RotatingFile)> {
+    let dir = log_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no per-user state directory"))?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("py_manager2.log");
+    let file = RotatingFile::open(&path, MAX_LOG_BYTES, KEPT_LOGS)?;
+    Ok((path, file))
+}
+
+// One formatted log event, as shown in the log panel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    // UTC wall-clock time, HH:MM:SS
+    pub time: String,
+    pub level: Level,
+    pub message: String,
+}
+
+impl LogLine {
+    pub fn new(level: Level, message: String) -> LogLine {
+        LogLine { time: clock_time(SystemTime::now()), level, message }
+    }
+}
+
+fn clock_time(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format!("{:02}:{:02}:{:02}", secs / 3600 % 24, secs / 60 % 60, secs % 60)
+}
+
+// Sends every event that passes the level filter to the app as `Event::Log`
+struct PanelLayer {
+    tx: UnboundedSender<Event>,
+}
+
+impl<S: Subscriber> Layer<S> for PanelLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let line = LogLine::new(*event.metadata().level(), visitor.finish());
+        let _ = self.tx.send(Event::Log(line));
+    }
+}
+
+// Renders the `message` field followed by the remaining fields as key=value
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        if self.fields.is_empty() {
+            self.message
+        } else if self.message.is_empty() {
+            self.fields.trim_start().to_string()
+        } else {
+            format!("{}{}", self.message, self.fields)
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_bytes: u64, keep: usize) -> io::Result<RotatingFile> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+            keep,
+        })
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}", n));
+        self.path.with_file_name(name)
+    }
+
+    // Shift log -> log.1 -> log.2 ..., dropping the oldest, and start a new file
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.keep));
+            for n in (1..self.keep).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_once_the_size_limit_is_reached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.log");
+        let mut log = RotatingFile::open(&path, 10, 2).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(dir.path().join("test.log.1")).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(dir.path().join("test.log.2")).unwrap(), "second\n");
+        assert!(!dir.path().join("test.log.3").exists());
+    }
+
+    #[test]
+    fn appends_to_an_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.log");
+        fs::write(&path, "old\n").unwrap();
+
+        let mut log = RotatingFile::open(&path, 1024, 1).unwrap();
+        log.write_all(b"new\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\nnew\n");
+    }
+}
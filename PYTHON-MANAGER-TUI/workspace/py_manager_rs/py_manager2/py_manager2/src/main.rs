@@ -1,462 +1,320 @@
-use reqwest::Client;
-use serde::Deserialize;
-use scraper::{Html, Selector};
-use std::collections::HashMap;
-use std::process::Command;
+use clap::Parser;
+use std::error::Error;
+use std::io;
+use std::path::PathBuf;
+use std::process;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::{info, warn};
+use terminal_guard::{Request, TerminalGuard};
+use tracing_subscriber::filter::LevelFilter;
+use py_manager_core::cache::InventoryCache;
+use py_manager_core::process::{CommandRunner, SystemRunner};
+use py_manager_core::{discovery, index, operations, site_packages};
 use tui::{
-    backend::TermionBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    backend::{Backend, CrosstermBackend},
     Terminal,
 };
-use termion::raw::IntoRawMode;
-use termion::input::TermRead;
-use termion::event::Key;
-use termion::screen::AlternateScreen;
-use std::fs::OpenOptions;
-use std::io::Write;
 
-#[derive(Debug, Deserialize)]
-struct PyPISearchResult {
-    name: String,
-}
-
-async fn search_pypi(query: &str) -> Vec<String> {
-    log_debug(format!("Searching PyPI for: {}", query));
-    let client = Client::new();
-    let url = format!("https://pypi.org/search/?q={}", query);
-    let response = match client.get(&url).send().await {
-        Ok(res) => res,
-        Err(e) => {
-            log_debug(format!("Failed to fetch from PyPI: {}", e));
-            return vec!["Failed to fetch packages from PyPI".to_string()];
+use py_manager2::app::{App, Task};
+use py_manager2::config::{Config, Layout};
+use py_manager2::event::{self, Event};
+use py_manager2::logging;
+use py_manager2::session::Session;
+use py_manager2::watch::FsWatcher;
+use py_manager2::{command, packages, ui};
+
+// Run a task in the background; whatever it finds out comes back as an event
+fn spawn_task(
+    task: Task,
+    runner: &Arc<dyn CommandRunner>,
+    config: &Config,
+    tx: &UnboundedSender<Event>,
+    store: &Option<Arc<InventoryCache>>,
+    watcher: &mut Option<FsWatcher>,
+) {
+    let tx = tx.clone();
+    let runner = Arc::clone(runner);
+    match task {
+        Task::RefreshPackages { version, known } => {
+            tokio::spawn(packages::refresh_packages(runner, config.backends, version, known, store.clone(), tx));
         }
-    };
-
-    if response.status().is_success() {
-        let text = match response.text().await {
-            Ok(t) => t,
-            Err(e) => {
-                log_debug(format!("Failed to read response from PyPI: {}", e));
-                return vec!["Failed to read response from PyPI".to_string()];
+        Task::RefreshCurrentVersion => {
+            tokio::spawn(async move {
+                let _ = tx.send(Event::CurrentVersion(discovery::get_current_python_version(runner.as_ref()).await));
+            });
+        }
+        // Runs `which` and `pip`, so never on the UI loop
+        Task::RefreshStatus => {
+            tokio::spawn(async move {
+                let (python_env, virtual_env) = read_status(runner.as_ref()).await;
+                let _ = tx.send(Event::StatusRead { python_env, virtual_env });
+            });
+        }
+        Task::RefreshVersions => {
+            tokio::spawn(async move {
+                let _ = tx.send(Event::VersionsChanged(discovery::get_python_versions(runner.as_ref()).await));
+            });
+        }
+        Task::ListAvailable => {
+            tokio::spawn(async move {
+                let _ = tx.send(Event::AvailableVersions(discovery::get_available_versions(runner.as_ref()).await));
+            });
+        }
+        Task::WatchSitePackages(versions) => {
+            if let Some(watcher) = watcher {
+                watcher.watch_site_packages(py_manager_core::packages::site_packages_by_version(&versions));
             }
-        };
-        let document = Html::parse_document(&text);
-        let selector = match Selector::parse(".package-snippet") {
-            Ok(sel) => sel,
-            Err(e) => {
-                log_debug(format!("Failed to parse HTML: {:?}", e));
-                return vec!["Failed to parse HTML".to_string()];
+        }
+        Task::InspectInterpreters(versions) => {
+            tokio::task::spawn_blocking(move || {
+                for version in versions {
+                    let interpreter = site_packages::inspect(&version);
+                    if tx.send(Event::InterpreterInspected { version, interpreter }).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // Needs the terminal, so the event loop handles it
+        Task::Suspend => {}
+        Task::ExpireToasts(after) => {
+            tokio::spawn(async move {
+                tokio::time::sleep(after).await;
+                let _ = tx.send(Event::ToastTimeout);
+            });
+        }
+        Task::SearchPyPI(query) => {
+            let index_url = config.index.url.clone();
+            tokio::spawn(async move {
+                let _ = tx.send(Event::SearchResults(index::search_pypi(&index_url, &query).await));
+            });
+        }
+        Task::Install(package) => {
+            tokio::spawn(async move {
+                let result = operations::install_package(runner.as_ref(), &package).await;
+                let _ = tx.send(Event::InstallFinished { package, result });
+            });
+        }
+        Task::Uninstall(package) => {
+            tokio::spawn(async move {
+                let result = operations::uninstall_package(runner.as_ref(), &package).await;
+                let _ = tx.send(Event::UninstallFinished { package, result });
+            });
+        }
+        Task::CreateVenv { version, path } => {
+            tokio::spawn(async move {
+                let result = operations::create_venv(runner.as_ref(), &version, &path).await;
+                let _ = tx.send(Event::VenvCreated { path, result });
+            });
+        }
+        Task::SaveHistory(history) => {
+            if let Some(path) = command::history_path() {
+                if let Err(e) = command::save_history(&path, &history) {
+                    warn!(error = %e, "failed to save the command history");
+                }
             }
-        };
-
-        let packages: Vec<String> = document
-            .select(&selector)
-            .map(|element| element.value().attr("data-name").unwrap_or("").to_string())
-            .collect();
-
-        log_debug(format!("Found packages: {:?}", packages));
-        packages
-    } else {
-        log_debug(format!("Failed to fetch packages from PyPI: status code {}", response.status()));
-        vec!["Failed to fetch packages from PyPI".to_string()]
-    }
-}
-
-async fn install_package(package: &str) -> Result<(), Box<dyn std::error::Error>> {
-    log_debug(format!("Installing package: {}", package));
-    let output = Command::new("pip")
-        .arg("install")
-        .arg(package)
-        .output()?;
-    
-    if output.status.success() {
-        log_debug(format!("Successfully installed package: {}", package));
-        Ok(())
-    } else {
-        log_debug(format!("Failed to install package: {}", String::from_utf8_lossy(&output.stderr)));
-        Err(format!("Failed to install package: {}", String::from_utf8_lossy(&output.stderr)).into())
+        }
+        Task::SaveLayout(layout) => {
+            if let Some(path) = Layout::saved_path() {
+                if let Err(e) = layout.save(&path) {
+                    warn!(error = %e, "failed to save the layout");
+                }
+            }
+        }
+        Task::Switch { version, scope } => {
+            tokio::spawn(async move {
+                let result = operations::switch_version(runner.as_ref(), &version, scope).await;
+                let switched = result.is_ok();
+                let _ = tx.send(Event::SwitchFinished { version, scope, result });
+                if !switched {
+                    return;
+                }
+                let _ = tx.send(Event::CurrentVersion(discovery::get_current_python_version(runner.as_ref()).await));
+            });
+        }
     }
 }
 
-async fn get_python_versions() -> Vec<String> {
-    let output = Command::new("pyenv")
-        .arg("versions")
-        .output()
-        .expect("Failed to execute pyenv");
-    let versions = String::from_utf8_lossy(&output.stdout);
-    versions
-        .lines()
-        .map(|line| line.trim().replace("* ", "").to_string())
-        .filter(|line| !line.is_empty())
-        .collect()
+// The environment and virtualenv for the status box, "Unknown" where they can't be read
+async fn read_status(runner: &dyn CommandRunner) -> (String, String) {
+    let python_env = discovery::get_python_env(runner).await.unwrap_or_else(|_| "Unknown".to_string());
+    let virtual_env = discovery::get_virtual_env(runner).await.unwrap_or_else(|_| "Unknown".to_string());
+    (python_env, virtual_env)
 }
 
-async fn get_packages_for_version(version: &str) -> Vec<String> {
-    log_debug(format!("Fetching packages for version: {}", version));
-    let clean_version = version.split_whitespace().next().unwrap_or("").to_string();
-    let output = Command::new("pyenv")
-        .arg("exec")
-        .arg("pip")
-        .arg("list")
-        .arg("--format=columns")
-        .env("PYENV_VERSION", &clean_version)
-        .output()
-        .expect("Failed to execute pip");
+async fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    guard: &TerminalGuard,
+    config: Config,
+    fresh: bool,
+    tx: UnboundedSender<Event>,
+    mut rx: UnboundedReceiver<Event>,
+) -> Result<(), Box<dyn Error>> {
+    let runner: Arc<dyn CommandRunner> = Arc::new(SystemRunner);
+
+    // Missing tools are reported in the UI instead of aborting startup
+    let mut startup_errors = Vec::new();
+    let versions = discovery::get_python_versions(runner.as_ref()).await.unwrap_or_else(|e| {
+        startup_errors.push(format!("Listing Python versions failed: {}", e));
+        Vec::new()
+    });
+    let current_python_version = discovery::get_current_python_version(runner.as_ref()).await.unwrap_or_else(|e| {
+        startup_errors.push(format!("Reading the active version failed: {}", e));
+        String::new()
+    });
+    let (python_env, virtual_env) = read_status(runner.as_ref()).await;
+
+    // The on-disk cache is optional: without it everything is simply read fresh
+    let store = match config.cache.inventory_path().map(|path| InventoryCache::open(&path)) {
+        Some(Ok(store)) => Some(Arc::new(store)),
+        Some(Err(e)) => {
+            warn!(error = %e, "failed to open package cache");
+            None
+        }
+        None => None,
+    };
+    let package_cache = match &store {
+        Some(store) => py_manager_core::packages::load_cached_inventories(&versions, store),
+        None => Default::default(),
+    };
 
-    if !output.status.success() {
-        log_debug(format!("Failed to fetch packages for version {}: {}", version, String::from_utf8_lossy(&output.stderr)));
-        return vec![format!("Failed to fetch packages: {}", String::from_utf8_lossy(&output.stderr))];
+    let mut app = App::new(versions, package_cache, current_python_version, python_env, virtual_env).with_config(config);
+    // https://no-color.org: keep the markers, drop the colors
+    if theme::no_color_requested() {
+        app.theme = app.theme.clone().without_color();
+    }
+    for message in startup_errors {
+        app.report_error(message);
+    }
+    // Resize events keep this up to date for mouse hit-testing
+    app.screen = terminal.size()?;
+    // The layout the last session ended with, unless starting fresh; a broken file
+    // falls back to the config's
+    match Layout::saved_path().filter(|_| !fresh).map(|path| Layout::load_saved(&path)) {
+        Some(Ok(Some(layout))) => app.layout = layout,
+        Some(Ok(None)) | None => {}
+        Some(Err(e)) => warn!(error = %e, "ignoring the saved layout"),
+    }
+    // Without a readable history the command line simply starts empty
+    match command::history_path().map(|path| command::load_history(&path)) {
+        Some(Ok(history)) => app.history = history,
+        Some(Err(e)) => warn!(error = %e, "failed to read the command history"),
+        None => {}
     }
 
-    let packages = String::from_utf8_lossy(&output.stdout);
-    log_debug(format!("Packages fetched for version {}: {}", version, packages));
-
-    packages
-        .lines()
-        .skip(2) // Skip the header lines
-        .map(|line| line.to_string())
-        .collect()
-}
-
-fn log_debug(message: String) {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("debug.log")
-        .expect("Failed to open debug log file");
-    writeln!(file, "{}", message).expect("Failed to write to debug log file");
-}
-
-async fn get_current_python_version() -> String {
-    let output = Command::new("pyenv")
-        .arg("global")
-        .output()
-        .expect("Failed to get current Python version");
-    String::from_utf8_lossy(&output.stdout).trim().to_string()
-}
-
-async fn get_python_env() -> String {
-    let output = Command::new("which")
-        .arg("python3")
-        .output()
-        .expect("Failed to get Python environment");
-    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if path.contains(".pyenv") {
-        "Pyenv".to_string()
-    } else if path.contains("/usr/local/") {
-        "Homebrew".to_string()
+    event::spawn_input(tx.clone());
+    let mut watcher = if app.config.backends.watch {
+        FsWatcher::new(tx.clone()).map_err(|e| warn!(error = %e, "file watching unavailable, polling instead")).ok()
     } else {
-        "System".to_string()
+        info!("file watching disabled in the config, polling instead");
+        None
+    };
+    if watcher.is_none() {
+        event::spawn_ticker(tx.clone(), app.config.timing.version_poll_interval());
+    }
+    let mut tasks = app.startup_tasks();
+    // Where the last session left off; without one the app starts at the first version
+    match Session::path().filter(|_| !fresh).map(|path| Session::load(&path)) {
+        Some(Ok(Some(session))) => tasks.extend(app.restore(session)),
+        Some(Ok(None)) | None => {}
+        Some(Err(e)) => warn!(error = %e, "ignoring the saved session"),
+    }
+    for task in tasks {
+        spawn_task(task, &runner, &app.config, &tx, &store, &mut watcher);
     }
-}
 
-async fn get_virtual_env() -> String {
-    let output = Command::new("pip")
-        .arg("--version")
-        .output()
-        .expect("Failed to get pip version");
-    let version_info = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    version_info.split_whitespace().last().unwrap_or("Unknown").to_string()
-}
+    terminal.clear()?;
+    loop {
+        // A panic in another thread gives the screen back; drawing stops with it
+        if app.dirty && terminal_guard::is_active() {
+            terminal.draw(|f| ui::draw(f, &app))?;
+            app.dirty = false;
+        }
 
-async fn draw_ui(
-    terminal: &mut Terminal<TermionBackend<AlternateScreen<termion::raw::RawTerminal<std::io::Stdout>>>>,
-    versions: Arc<Vec<String>>,
-    selected_version_index: Arc<RwLock<usize>>,
-    package_cache: Arc<RwLock<HashMap<String, Vec<String>>>>,
-    current_python_version: &str,
-    python_env: &str,
-    virtual_env: &str,
-    show_popup: bool,
-    popup_message: &str,
-    pypi_packages: &Vec<String>,
-    selected_package_index: usize,
-    show_pypi: bool,
-    loading: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let selected_version_index = *selected_version_index.read().await;
-    let version_items: Vec<ListItem> = versions
-        .iter()
-        .enumerate()
-        .map(|(i, v)| {
-            let style = if v.contains(&current_python_version) {
-                Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)
-            } else if i == selected_version_index {
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        let event = match rx.recv().await {
+            Some(event) => event,
+            None => break,
+        };
+        for task in app.handle_event(event) {
+            if task == Task::Suspend {
+                guard.suspend()?;
+                terminal.clear()?;
+                app.dirty = true;
             } else {
-                Style::default()
-            };
-            ListItem::new(v.clone()).style(style)
-        })
-        .collect();
-
-    let packages = package_cache.read().await.get(&versions[selected_version_index]).cloned().unwrap_or_else(|| vec![String::from("Loading...")]);
-    let package_items: Vec<ListItem> = packages.iter().map(|p| ListItem::new(p.clone()).style(Style::default().fg(Color::Yellow))).collect();
-
-    terminal.draw(|f| {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(70),
-                Constraint::Percentage(20),
-                Constraint::Percentage(10),
-            ].as_ref())
-            .split(f.size());
-
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-            .split(chunks[0]);
-
-        let versions_list = List::new(version_items)
-            .block(Block::default().title("Python Versions").borders(Borders::ALL).style(Style::default().fg(Color::Cyan)));
-        f.render_widget(versions_list, main_chunks[0]);
-
-        let packages_list = List::new(package_items)
-            .block(Block::default().title("Packages").borders(Borders::ALL).style(Style::default().fg(Color::Yellow)));
-        f.render_widget(packages_list, main_chunks[1]);
-
-        let status_text = format!(
-            "Current Python Version: {}\nPython Environment: {}\nVirtual Environment: {}",
-            current_python_version, python_env, virtual_env
-        );
-        let status_block = Paragraph::new(status_text)
-            .block(Block::default().title("Status").borders(Borders::ALL));
-        f.render_widget(status_block, chunks[2]);
-
-        if show_popup {
-            let popup_block = Paragraph::new(popup_message)
-                .block(Block::default().title("Options").borders(Borders::ALL).style(Style::default().fg(Color::Red)));
-            f.render_widget(popup_block, chunks[1]);
+                spawn_task(task, &runner, &app.config, &tx, &store, &mut watcher);
+            }
         }
-
-        if show_pypi {
-            let pypi_items: Vec<ListItem> = pypi_packages.iter().enumerate().map(|(i, p)| {
-                let style = if i == selected_package_index {
-                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                };
-                ListItem::new(p.clone()).style(style)
-            }).collect();
-
-            let pypi_list = List::new(pypi_items)
-                .block(Block::default().title("PyPI Packages").borders(Borders::ALL).style(Style::default().fg(Color::Green)));
-            f.render_widget(pypi_list, chunks[1]);
+        if app.should_quit {
+            break;
         }
-
-        if loading {
-            let loading_block = Paragraph::new("Loading...").block(Block::default().title("Status").borders(Borders::ALL));
-            f.render_widget(loading_block, chunks[2]);
+    }
+    if let Some(path) = Session::path() {
+        if let Err(e) = app.session().save(&path) {
+            warn!(error = %e, "failed to save the session");
         }
-    })?;
-
+    }
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    log_debug("Starting application".to_string());
-    let stdout = std::io::stdout().into_raw_mode()?;
-    let stdin = termion::async_stdin();
-    let backend = TermionBackend::new(AlternateScreen::from(stdout));
-    let mut terminal = Terminal::new(backend)?;
-
-    let versions = Arc::new(get_python_versions().await);
-    let selected_version_index = Arc::new(RwLock::new(0));
-    let package_cache: Arc<RwLock<HashMap<String, Vec<String>>>> = Arc::new(RwLock::new(HashMap::new()));
-    let loading_packages: Arc<RwLock<bool>> = Arc::new(RwLock::new(true));
-
-    let mut current_python_version = get_current_python_version().await;
-    let python_env = get_python_env().await;
-    let virtual_env = get_virtual_env().await;
-
-    let mut show_popup = false;
-    let mut popup_message = String::new();
-    let mut show_pypi = false;
-    let mut pypi_packages = Vec::new();
-    let mut selected_package_index = 0;
-    let mut loading = false;
+#[derive(Parser)]
+#[command(version, about = "Browse pyenv interpreters and their packages")]
+struct Args {
+    /// Minimum level written to the log: off, error, warn, info, debug or trace
+    #[arg(long, default_value = "info", value_name = "LEVEL")]
+    log_level: LevelFilter,
+    /// Read settings from this file instead of ~/.config/py_manager/config.toml
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Print the effective configuration, defaults included, and exit
+    #[arg(long)]
+    print_config: bool,
+    /// Start at the first version and the config's layout instead of where the last session left off
+    #[arg(long)]
+    fresh: bool,
+}
 
-    // Prefetch packages in the background
-    {
-        let package_cache_clone = Arc::clone(&package_cache);
-        let versions_clone = Arc::clone(&versions);
-        tokio::spawn(async move {
-            for version in versions_clone.iter() {
-                let pkgs = get_packages_for_version(version).await;
-                package_cache_clone.write().await.insert(version.clone(), pkgs);
-            }
-        });
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    // A broken config stops startup before the screen is taken over, so the error stays readable
+    let config = match Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    if args.print_config {
+        print!("{}", config.effective_toml());
+        return Ok(());
     }
-
-    // Load initial packages
-    {
-        let package_cache_clone = Arc::clone(&package_cache);
-        let loading_packages_clone = Arc::clone(&loading_packages);
-        let version = versions[*selected_version_index.read().await].clone();
-        tokio::spawn(async move {
-            let pkgs = get_packages_for_version(&version).await;
-            package_cache_clone.write().await.insert(version.clone(), pkgs);
-            *loading_packages_clone.write().await = false;
-        });
+    let (tx, rx) = mpsc::unbounded_channel();
+    // Logging is best effort; the TUI works the same without it
+    match logging::init(args.log_level, tx.clone()) {
+        Ok(path) => info!(log_file = %path.display(), "starting"),
+        Err(e) => eprintln!("Logging disabled: {}", e),
     }
-
-    terminal.clear()?;
-    let mut keys = stdin.keys();
-    let tick_rate = Duration::from_millis(100);
-    let mut last_tick = Instant::now();
-
-    loop {
-        let new_python_version = get_current_python_version().await;
-        if new_python_version != current_python_version {
-            current_python_version = new_python_version;
-            let mut index = selected_version_index.write().await;
-            if let Some(new_index) = versions.iter().position(|v| *v == current_python_version) {
-                *index = new_index;
-                let version = versions[*index].clone();
-                let package_cache_clone = Arc::clone(&package_cache);
-                tokio::spawn(async move {
-                    let pkgs = get_packages_for_version(&version).await;
-                    package_cache_clone.write().await.insert(version.clone(), pkgs);
-                });
-            }
+    // Restores the screen on return and on panic; signals come back through the
+    // event loop, so quitting still saves the session
+    let guard = TerminalGuard::enter(config.mouse.capture)?;
+    guard.on_request({
+        let tx = tx.clone();
+        move |request| {
+            let _ = tx.send(match request {
+                Request::Quit => Event::Quit,
+                Request::Suspend => Event::Suspend,
+            });
         }
+    });
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
 
-        draw_ui(
-            &mut terminal,
-            Arc::clone(&versions),
-            Arc::clone(&selected_version_index),
-            Arc::clone(&package_cache),
-            &current_python_version,
-            &python_env,
-            &virtual_env,
-            show_popup,
-            &popup_message,
-            &pypi_packages,
-            selected_package_index,
-            show_pypi,
-            loading,
-        ).await?;
+    let res = run_app(&mut terminal, &guard, config, args.fresh, tx, rx).await;
+    drop(guard);
 
-        if let Some(Ok(key)) = keys.next() {
-            match key {
-                Key::Char('q') => {
-                    terminal.clear()?;
-                    log_debug("Exiting application".to_string());
-                    return Ok(());
-                }
-                Key::Char('j') => {
-                    let mut index = selected_version_index.write().await;
-                    if show_pypi {
-                        if selected_package_index < pypi_packages.len() - 1 {
-                            selected_package_index += 1;
-                        }
-                    } else if *index < versions.len() - 1 {
-                        *index += 1;
-                        let version = versions[*index].clone();
-                        let package_cache_clone = Arc::clone(&package_cache);
-                        tokio::spawn(async move {
-                            let pkgs = get_packages_for_version(&version).await;
-                            package_cache_clone.write().await.insert(version.clone(), pkgs);
-                        });
-                    }
-                }
-                Key::Char('k') => {
-                    let mut index = selected_version_index.write().await;
-                    if show_pypi {
-                        if selected_package_index > 0 {
-                            selected_package_index -= 1;
-                        }
-                    } else if *index > 0 {
-                        *index -= 1;
-                        let version = versions[*index].clone();
-                        let package_cache_clone = Arc::clone(&package_cache);
-                        tokio::spawn(async move {
-                            let pkgs = get_packages_for_version(&version).await;
-                            package_cache_clone.write().await.insert(version.clone(), pkgs);
-                        });
-                    }
-                }
-                Key::Char('\n') => {
-                    if show_pypi {
-                        let selected_package = &pypi_packages[selected_package_index];
-                        match install_package(selected_package).await {
-                            Ok(_) => {
-                                popup_message = format!("Successfully installed {}", selected_package);
-                                log_debug(format!("Successfully installed {}", selected_package));
-                            },
-                            Err(e) => {
-                                popup_message = format!("Failed to install {}: {}", selected_package, e);
-                                log_debug(format!("Failed to install {}: {}", selected_package, e));
-                            },
-                        }
-                        show_popup = true;
-                        show_pypi = false;
-                    } else {
-                        show_popup = true;
-                        popup_message = String::from("1. Switch to this version\n2. Add packages to this version\nPress 1 or 2 to choose, or q to cancel");
-                    }
-                }
-                Key::Char('1') => {
-                    if show_popup {
-                        let index = *selected_version_index.read().await;
-                        let version = versions[index].clone();
-                        Command::new("pyenv")
-                            .arg("global")
-                            .arg(&version)
-                            .output()
-                            .expect("Failed to switch Python version");
-                        current_python_version = get_current_python_version().await;
-                        show_popup = false;
-                    }
-                }
-                Key::Char('2') => {
-                    if show_popup {
-                        show_popup = false;
-                        show_pypi = true;
-                        loading = true;
-                        pypi_packages = search_pypi("").await;
-                        loading = false;
-                    }
-                }
-                Key::Char('s') => {
-                    if show_pypi {
-                        loading = true;
-                        pypi_packages = search_pypi("").await;
-                        loading = false;
-                    }
-                }
-                Key::Char('a') => {
-                    if show_pypi {
-                        let mut query = String::new();
-                        while let Some(Ok(key)) = keys.next() {
-                            match key {
-                                Key::Char('\n') => break,
-                                Key::Char(c) => query.push(c),
-                                Key::Backspace => {
-                                    query.pop();
-                                }
-                                _ => {}
-                            }
-                            loading = true;
-                            pypi_packages = search_pypi(&query).await;
-                            loading = false;
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
-        }
+    if let Err(err) = res {
+        println!("{:?}", err);
     }
-}
 
+    Ok(())
+}
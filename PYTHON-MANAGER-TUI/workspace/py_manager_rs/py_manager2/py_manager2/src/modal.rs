@@ -0,0 +1,251 @@
+// Dialogs stacked over the panes: a yes/no confirmation, a pick from a few
+// choices, a line of text, and a progress box for work in flight. The one on top
+// takes every key and click until it is answered or dismissed, so nothing behind
+// it reacts; each kind handles its own keys and asks the app for an `Intent`.
+
+use crossterm::event::{KeyCode, KeyEvent};
+use keymap::{Action, Keymap};
+
+use crate::app::Hint;
+
+// What answering a dialog asks the app to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intent {
+    Switch(String),
+    // Open the PyPI list for the selected version
+    AddPackages,
+    Install(String),
+    Uninstall(String),
+    SearchPyPI(String),
+}
+
+// What a text field is for, which decides what its text becomes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    SearchPyPI,
+}
+
+impl Prompt {
+    fn intent(self, text: String) -> Intent {
+        match self {
+            Prompt::SearchPyPI => Intent::SearchPyPI(text),
+        }
+    }
+
+    fn submit_hint(self) -> &'static str {
+        match self {
+            Prompt::SearchPyPI => "Search for the query",
+        }
+    }
+}
+
+// Work a progress box waits for; its result closes the box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    Search,
+    Install,
+    Uninstall,
+    CreateVenv,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Choice {
+    pub label: &'static str,
+    // Picks the choice directly; without one it's picked by its number
+    pub action: Option<Action>,
+    pub intent: Intent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Modal {
+    Confirm { title: String, message: String, intent: Intent },
+    Choice { title: String, choices: Vec<Choice>, selected: usize },
+    Input { title: String, prompt: Prompt, value: String },
+    // Esc only hides it; the work carries on and reports back as usual
+    Progress { title: String, message: String, job: Job },
+}
+
+// What a key or click did to the dialog on top
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    // Still open, possibly changed
+    Open,
+    Dismissed,
+    Done(Intent),
+}
+
+impl Modal {
+    pub fn title(&self) -> &str {
+        match self {
+            Modal::Confirm { title, .. }
+            | Modal::Choice { title, .. }
+            | Modal::Input { title, .. }
+            | Modal::Progress { title, .. } => title,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, keymap: &Keymap) -> Outcome {
+        let action = keymap.action(key);
+        match self {
+            // Text goes into the field as typed, so the keymap doesn't apply
+            Modal::Input { prompt, value, .. } => match key.code {
+                KeyCode::Enter => Outcome::Done(prompt.intent(value.clone())),
+                KeyCode::Esc => Outcome::Dismissed,
+                KeyCode::Backspace => {
+                    value.pop();
+                    Outcome::Open
+                }
+                KeyCode::Char(c) => {
+                    value.push(c);
+                    Outcome::Open
+                }
+                _ => Outcome::Open,
+            },
+            Modal::Confirm { intent, .. } => match (action, key.code) {
+                (Some(Action::Confirm), _) | (_, KeyCode::Char('y')) => Outcome::Done(intent.clone()),
+                (Some(Action::Cancel), _) | (_, KeyCode::Char('n')) => Outcome::Dismissed,
+                _ => Outcome::Open,
+            },
+            Modal::Choice { choices, selected, .. } => {
+                let picked = match (action, key.code) {
+                    (Some(action), _) if choices.iter().any(|choice| choice.action == Some(action)) => {
+                        choices.iter().position(|choice| choice.action == Some(action))
+                    }
+                    (_, KeyCode::Char(digit @ '1'..='9')) => {
+                        let index = digit as usize - '1' as usize;
+                        choices.get(index).filter(|choice| choice.action.is_none()).map(|_| index)
+                    }
+                    (Some(Action::Confirm), _) => Some(*selected),
+                    (Some(Action::Down), _) => {
+                        *selected = (*selected + 1).min(choices.len().saturating_sub(1));
+                        None
+                    }
+                    (Some(Action::Up), _) => {
+                        *selected = selected.saturating_sub(1);
+                        None
+                    }
+                    (Some(Action::Cancel), _) => return Outcome::Dismissed,
+                    _ => None,
+                };
+                match picked.and_then(|index| choices.get(index)) {
+                    Some(choice) => Outcome::Done(choice.intent.clone()),
+                    None => Outcome::Open,
+                }
+            }
+            Modal::Progress { .. } if action == Some(Action::Cancel) => Outcome::Dismissed,
+            Modal::Progress { .. } => Outcome::Open,
+        }
+    }
+
+    // A click on `row` inside the border; only choices can be clicked
+    pub fn click(&mut self, row: usize) -> Outcome {
+        match self {
+            Modal::Choice { choices, .. } => match choices.get(row) {
+                Some(choice) => Outcome::Done(choice.intent.clone()),
+                None => Outcome::Open,
+            },
+            _ => Outcome::Open,
+        }
+    }
+
+    // The key that picks a choice, as shown in front of it
+    pub fn choice_key(choice: &Choice, index: usize, keymap: &Keymap) -> String {
+        match choice.action {
+            Some(action) => keymap.keys(action).first().map_or_else(|| String::from("?"), |key| key.to_string()),
+            None => (index + 1).to_string(),
+        }
+    }
+
+    // Every key the dialog reacts to, for the footer and the help overlay
+    pub fn hints(&self, keymap: &Keymap) -> Vec<Hint> {
+        let hint = |action: Option<Action>, keys: String, description: &'static str| Hint { action, keys, description };
+        match self {
+            Modal::Confirm { .. } => vec![
+                hint(Some(Action::Confirm), format!("{}/y", keymap.describe_keys(Action::Confirm)), "Yes"),
+                hint(Some(Action::Cancel), format!("{}/n", keymap.describe_keys(Action::Cancel)), "No"),
+            ],
+            Modal::Choice { choices, .. } => {
+                let mut hints: Vec<Hint> = choices
+                    .iter()
+                    .enumerate()
+                    .map(|(i, choice)| hint(choice.action, Modal::choice_key(choice, i, keymap), choice.label))
+                    .collect();
+                hints.push(hint(Some(Action::Down), keymap.describe_keys(Action::Down), "Next choice"));
+                hints.push(hint(Some(Action::Up), keymap.describe_keys(Action::Up), "Previous choice"));
+                hints.push(hint(Some(Action::Confirm), keymap.describe_keys(Action::Confirm), "Pick the highlighted choice"));
+                hints.push(hint(Some(Action::Cancel), keymap.describe_keys(Action::Cancel), "Close"));
+                hints
+            }
+            Modal::Input { prompt, .. } => vec![
+                hint(None, String::from("enter"), prompt.submit_hint()),
+                hint(None, String::from("esc"), "Cancel"),
+                hint(None, String::from("backspace"), "Delete the last character"),
+            ],
+            Modal::Progress { .. } => {
+                vec![hint(Some(Action::Cancel), keymap.describe_keys(Action::Cancel), "Hide; the work carries on")]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn options() -> Modal {
+        Modal::Choice {
+            title: String::from("Options"),
+            choices: vec![
+                Choice { label: "Switch", action: Some(Action::SwitchVersion), intent: Intent::Switch(String::from("3.12.4")) },
+                Choice { label: "Add", action: None, intent: Intent::AddPackages },
+            ],
+            selected: 0,
+        }
+    }
+
+    #[test]
+    fn confirmations_take_enter_or_y_and_esc_or_n() {
+        let keymap = Keymap::default();
+        let mut confirm = Modal::Confirm {
+            title: String::from("Uninstall"),
+            message: String::from("Uninstall rich?"),
+            intent: Intent::Uninstall(String::from("rich")),
+        };
+        assert_eq!(confirm.handle_key(key(KeyCode::Char('j')), &keymap), Outcome::Open);
+        assert_eq!(confirm.handle_key(key(KeyCode::Char('y')), &keymap), Outcome::Done(Intent::Uninstall(String::from("rich"))));
+        assert_eq!(confirm.handle_key(key(KeyCode::Enter), &keymap), Outcome::Done(Intent::Uninstall(String::from("rich"))));
+        assert_eq!(confirm.handle_key(key(KeyCode::Char('n')), &keymap), Outcome::Dismissed);
+        assert_eq!(confirm.handle_key(key(KeyCode::Esc), &keymap), Outcome::Dismissed);
+    }
+
+    #[test]
+    fn choices_are_picked_by_key_number_or_cursor() {
+        let keymap = Keymap::default();
+        assert_eq!(options().handle_key(key(KeyCode::Char('1')), &keymap), Outcome::Done(Intent::Switch(String::from("3.12.4"))));
+        assert_eq!(options().handle_key(key(KeyCode::Char('2')), &keymap), Outcome::Done(Intent::AddPackages));
+        assert_eq!(options().handle_key(key(KeyCode::Char('3')), &keymap), Outcome::Open);
+
+        let mut choice = options();
+        choice.handle_key(key(KeyCode::Down), &keymap);
+        choice.handle_key(key(KeyCode::Down), &keymap);
+        assert_eq!(choice.handle_key(key(KeyCode::Enter), &keymap), Outcome::Done(Intent::AddPackages));
+        assert_eq!(choice.click(0), Outcome::Done(Intent::Switch(String::from("3.12.4"))));
+        assert_eq!(choice.handle_key(key(KeyCode::Char('q')), &keymap), Outcome::Open);
+    }
+
+    #[test]
+    fn text_fields_take_every_printable_key() {
+        let keymap = Keymap::default();
+        let mut input = Modal::Input { title: String::from("Search PyPI"), prompt: Prompt::SearchPyPI, value: String::new() };
+        for c in "q?:x".chars() {
+            assert_eq!(input.handle_key(key(KeyCode::Char(c)), &keymap), Outcome::Open);
+        }
+        input.handle_key(key(KeyCode::Backspace), &keymap);
+        assert_eq!(input.handle_key(key(KeyCode::Enter), &keymap), Outcome::Done(Intent::SearchPyPI(String::from("q?:"))));
+    }
+}
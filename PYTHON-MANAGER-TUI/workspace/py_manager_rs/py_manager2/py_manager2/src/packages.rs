@@ -0,0 +1,64 @@
+// Refreshing a version's package list in the background and reporting back to the
+// app; reading and caching the lists themselves lives in py_manager_core.
+
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+
+use py_manager_core::cache::{Freshness, Inventory, InventoryCache};
+use py_manager_core::packages::{clean_version, get_packages_for_version, interpreter_key};
+use py_manager_core::process::CommandRunner;
+use py_manager_core::site_packages;
+
+use crate::config::Backends;
+use crate::event::Event;
+
+// What the app already knows about a version's inventory when it asks for a refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Known {
+    pub site_packages_fingerprint: Option<i64>,
+    pub freshness: Freshness,
+}
+
+// Re-read a version's packages unless its site-packages are unchanged since the copy
+// the app holds, reporting progress back as events
+pub async fn refresh_packages(
+    runner: Arc<dyn CommandRunner>,
+    backends: Backends,
+    version: String,
+    known: Option<Known>,
+    store: Option<Arc<InventoryCache>>,
+    tx: UnboundedSender<Event>,
+) {
+    let clean_version = clean_version(&version);
+    let site_packages_fingerprint = site_packages::version_prefix(&clean_version)
+        .and_then(|prefix| site_packages::fingerprint(&site_packages::site_packages_dirs(&prefix)));
+
+    if let Some(known) = known {
+        // Without a fingerprint (pip fallback) only this session's reads can be trusted
+        let unchanged = known.site_packages_fingerprint == site_packages_fingerprint
+            && (site_packages_fingerprint.is_some() || known.freshness == Freshness::Fresh);
+        if unchanged {
+            let _ = tx.send(Event::PackagesUnchanged(version));
+            return;
+        }
+    }
+    let _ = tx.send(Event::PackagesRefreshing(version.clone()));
+
+    match get_packages_for_version(runner.as_ref(), backends.sources(), &version).await {
+        Ok(packages) => {
+            if let Some(store) = &store {
+                if let Err(e) = store.store(&interpreter_key(&clean_version), site_packages_fingerprint, &packages) {
+                    warn!(version = %version, error = %e, "failed to cache packages");
+                }
+            }
+            let inventory = Inventory { packages, site_packages_fingerprint, freshness: Freshness::Fresh };
+            let _ = tx.send(Event::PackagesLoaded { version, inventory });
+        }
+        // Errors stay out of the on-disk cache so the next refresh retries
+        Err(error) => {
+            warn!(version = %version, %error, "failed to fetch packages");
+            let _ = tx.send(Event::PackagesFailed { version, error });
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Where the last session left off: the tab order and pins, the selected version
+// and package, which list had the keys, the PyPI list with its query and selected
+// package, the available versions and the log panel with its filter, and the `/`
+// search of each list. Saved on exit to $XDG_STATE_HOME/py_manager/session.toml
+// and put back on the next start unless `--fresh` is given. Versions and packages
+// are kept by name, so ones that are gone by then are simply not selected.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    // Bare version names in the order their tabs were left in
+    pub tabs: Vec<String>,
+    pub pinned: Vec<String>,
+    pub version: Option<String>,
+    // The package under the cursor on the version's page, by name, and whether
+    // the page's packages had the keys
+    pub installed_package: Option<String>,
+    pub packages_focused: bool,
+    // The PyPI list was open, and what it was searched for
+    pub show_pypi: bool,
+    pub query: String,
+    pub package: Option<String>,
+    pub show_available: bool,
+    pub show_log: bool,
+    // Most verbose level the log panel showed: trace, debug, info, warn or error
+    pub log_filter: String,
+    pub zoom: bool,
+    // Each list's `/` search by the list's name, for the lists that had one
+    pub searches: BTreeMap<String, SavedSearch>,
+}
+
+// A list's query and whether it hid the rows that didn't match
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSearch {
+    pub query: String,
+    pub filter: bool,
+}
+
+impl Session {
+    // ~/.local/state/py_manager/session.toml on Linux, next to the saved layout
+    pub fn path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("py_manager").join("session.toml"))
+    }
+
+    // None when no session was saved yet
+    pub fn load(path: &Path) -> io::Result<Option<Session>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&text).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_round_trip_and_a_missing_file_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("session.toml");
+        assert_eq!(Session::load(&path).unwrap(), None);
+
+        let session = Session {
+            tabs: vec![String::from("3.12.4"), String::from("system")],
+            pinned: vec![String::from("3.12.4")],
+            version: Some(String::from("system")),
+            installed_package: Some(String::from("requests")),
+            packages_focused: true,
+            show_pypi: true,
+            query: String::from("requests"),
+            package: Some(String::from("requests-oauthlib")),
+            show_available: true,
+            show_log: false,
+            log_filter: String::from("warn"),
+            zoom: true,
+            searches: BTreeMap::from([
+                (String::from("versions"), SavedSearch { query: String::from("3.1"), filter: true }),
+                (String::from("log"), SavedSearch { query: String::from("failed"), filter: false }),
+            ]),
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), Some(session));
+
+        // Fields added later fall back to their defaults
+        fs::write(&path, "version = \"3.11.7\"\n").unwrap();
+        let loaded = Session::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.version.as_deref(), Some("3.11.7"));
+        assert!(loaded.tabs.is_empty());
+
+        fs::write(&path, "tabs = 3\n").unwrap();
+        assert_eq!(Session::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,725 @@
+use py_manager_core::packages::clean_version;
+use py_manager_core::site_packages::normalize_name;
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::Level;
+use tui::{
+    backend::Backend,
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Widget},
+    Frame,
+};
+
+use crate::app::{App, ListId};
+use crate::config;
+use crate::list::StatefulList;
+use crate::modal::Modal;
+use keymap::{Action, Keymap};
+
+// What the grow, shrink and zoom keys act on, depending on focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    // The version list; zooming it keeps the selected version's page beside it
+    Versions,
+    // Whatever the middle panel shows: options, PyPI packages, log or diff
+    Panel,
+}
+
+// Where each pane goes; also used to find what a mouse click hit. Panes hidden by
+// a zoom get an empty area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Areas {
+    // One tab per version along the top line
+    pub tabs: Rect,
+    // Versions beside the selected version's page: its interpreter and packages
+    pub main: Rect,
+    pub versions: Rect,
+    pub details: Rect,
+    pub packages: Rect,
+    // PyPI list, log or diff
+    pub panel: Rect,
+    pub status: Rect,
+    pub footer: Rect,
+}
+
+// Below this many columns only the focused pane is shown above the status box, and
+// the version list gives way to the selected version's page; the tab bar still
+// switches between versions
+pub const NARROW_WIDTH: u16 = 60;
+// Smallest screen anything is laid out on; below it only a note is drawn
+pub const MIN_WIDTH: u16 = 30;
+pub const MIN_HEIGHT: u16 = 10;
+
+pub fn fits(size: Rect) -> bool {
+    size.width >= MIN_WIDTH && size.height >= MIN_HEIGHT
+}
+
+impl Areas {
+    // `status_lines` is how many lines the status box shows inside its border
+    pub fn new(size: Rect, layout: config::Layout, focus: Pane, zoom: bool, status_lines: u16) -> Areas {
+        let narrow = size.width < NARROW_WIDTH;
+        // The tab bar keeps the top line and the key-hint footer the bottom one
+        let screen = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(size);
+
+        let (main, panel, status) = match (zoom, focus) {
+            (true, Pane::Versions) => (screen[1], Rect::default(), Rect::default()),
+            (true, Pane::Panel) => (Rect::default(), screen[1], Rect::default()),
+            (false, _) => {
+                // The status box gets its share but never less than its lines need;
+                // the rest is split between main and panel in their proportions
+                let height = screen[1].height;
+                let share = |total: u16, percent: u16, of: u16| (u32::from(total) * u32::from(percent) / u32::from(of.max(1))) as u16;
+                let status = share(height, layout.status, 100).max(status_lines + 2).min(height);
+                let rest = height - status;
+                let panel = match (narrow, focus) {
+                    (true, Pane::Versions) => 0,
+                    (true, Pane::Panel) => rest,
+                    (false, _) => share(rest, layout.panel, layout.main + layout.panel),
+                };
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(rest - panel), Constraint::Length(panel), Constraint::Length(status)].as_ref())
+                    .split(screen[1]);
+                (chunks[0], chunks[1], chunks[2])
+            }
+        };
+
+        let (versions, page) = if narrow {
+            (Rect::default(), main)
+        } else {
+            let direction = match layout.split {
+                config::Split::Horizontal => Direction::Horizontal,
+                config::Split::Vertical => Direction::Vertical,
+            };
+            let main_chunks = Layout::default()
+                .direction(direction)
+                .constraints([Constraint::Percentage(layout.versions), Constraint::Percentage(100 - layout.versions)].as_ref())
+                .split(main);
+            (main_chunks[0], main_chunks[1])
+        };
+
+        // Three lines of interpreter details inside a border, left out when they
+        // would leave the packages fewer than three rows
+        let details = if page.height >= 5 + 5 { 5 } else { 0 };
+        let page = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(details), Constraint::Min(0)].as_ref())
+            .split(page);
+
+        Areas {
+            tabs: screen[0],
+            main,
+            versions,
+            details: page[0],
+            packages: page[1],
+            panel,
+            status,
+            footer: screen[2],
+        }
+    }
+}
+
+// The status box's lines: the error while there is one, above the three about the
+// active Python
+pub fn status_lines(app: &App) -> u16 {
+    3 + u16::from(app.error_message.is_some())
+}
+
+// `text` cut to `width` characters, ending in … when something was cut off
+pub fn ellipsize(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    match width {
+        0 => String::new(),
+        _ => text.chars().take(width - 1).chain(['…']).collect(),
+    }
+}
+
+// A "name  version" row that doesn't fit loses the end of its name rather than
+// its version, so versions are never cut in half
+pub fn fit_package(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    let mut fields = line.split_whitespace();
+    match (fields.next(), fields.next_back()) {
+        (Some(name), Some(version)) if width > version.chars().count() + 2 => {
+            format!("{} {}", ellipsize(name, width - version.chars().count() - 1), version)
+        }
+        _ => ellipsize(line, width),
+    }
+}
+
+// Row `y` counted from the first line inside `area`'s border, if it falls there
+pub fn row_in(area: Rect, x: u16, y: u16) -> Option<usize> {
+    let inside = x > area.x && x + 1 < area.x + area.width && y > area.y && y + 1 < area.y + area.height;
+    inside.then(|| (y - area.y - 1) as usize)
+}
+
+// " 1 system ", " 2 •*3.12.4 ": the number jumps to the tab, the dot marks a pin
+// and the star the version in use
+fn tab_title(app: &App, index: usize) -> String {
+    let version = clean_version(&app.versions.items[index]);
+    let number = if index < 9 { format!("{} ", index + 1) } else { String::new() };
+    let pin = if app.pinned.contains(&version) { "•" } else { "" };
+    let active = if version == app.current_python_version { "*" } else { "" };
+    format!(" {}{}{}{} ", number, pin, active, version)
+}
+
+// The tabs that fit in a bar `width` columns wide, as (version index, x offset,
+// width). Tabs scroll off to the left so that the selected one is always shown.
+pub fn tab_positions(app: &App, width: u16) -> Vec<(usize, u16, u16)> {
+    // Each tab is followed by a one-column divider
+    let widths: Vec<u16> = (0..app.versions.len()).map(|i| tab_title(app, i).chars().count() as u16 + 1).collect();
+    let selected = app.versions.selected().unwrap_or(0);
+    let mut first = 0;
+    while first < selected && widths[first..=selected].iter().sum::<u16>() > width {
+        first += 1;
+    }
+    let mut x = 0;
+    let mut positions = Vec::new();
+    for (i, tab_width) in widths.iter().enumerate().skip(first) {
+        if x >= width {
+            break;
+        }
+        positions.push((i, x, tab_width - 1));
+        x += tab_width;
+    }
+    positions
+}
+
+fn draw_tabs<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let mut spans = Vec::new();
+    for (i, _, _) in tab_positions(app, area.width) {
+        let style = if Some(i) == app.versions.selected() {
+            app.theme.tab()
+        } else if app.current_python_version == clean_version(&app.versions.items[i]) {
+            app.theme.active()
+        } else {
+            Style::default()
+        };
+        spans.push(Span::styled(tab_title(app, i), style));
+        spans.push(Span::raw("│"));
+    }
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
+
+// A path with the home directory written as ~, to keep the details pane narrow
+fn tilde(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rest) => Path::new("~").join(rest).display().to_string(),
+        None => path.display().to_string(),
+    }
+}
+
+// Where the selected version lives and whether it looks usable
+fn draw_details<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let version = app.selected_version().map(|v| clean_version(v)).unwrap_or_default();
+    let interpreter = app.interpreters.get(&version);
+    let location = match interpreter {
+        Some(interpreter) => match &interpreter.prefix {
+            Some(prefix) => tilde(prefix),
+            None => String::from("outside pyenv"),
+        },
+        None if app.versions.is_empty() => String::new(),
+        None => String::from("..."),
+    };
+    let site_packages = match interpreter.map(|interpreter| interpreter.site_packages.as_slice()) {
+        Some([]) | None => String::new(),
+        Some([only]) => tilde(only),
+        Some([first, rest @ ..]) => format!("{} (+{} more)", tilde(first), rest.len()),
+    };
+    let width = area.width.saturating_sub(2) as usize;
+    let mut lines = vec![
+        Spans::from(ellipsize(&format!("Location: {}", location), width)),
+        Spans::from(ellipsize(&format!("Site-packages: {}", site_packages), width)),
+    ];
+    if let Some((level, health)) = app.health() {
+        let health = ellipsize(&health, width.saturating_sub("Health: ".len()));
+        lines.push(Spans::from(vec![Span::raw("Health: "), Span::styled(health, level_style(app, level))]));
+    }
+    let title = if version.is_empty() { String::from("Interpreter") } else { format!("Interpreter {}", version) };
+    let details = Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL).style(app.theme.accent()));
+    f.render_widget(details, area);
+}
+
+// Installed versions of the selected version's packages, keyed the way PyPI
+// names are compared
+fn installed_packages(app: &App) -> HashMap<String, String> {
+    app.selected_inventory()
+        .map(|inventory| {
+            inventory
+                .packages
+                .iter()
+                .filter_map(|line| {
+                    let mut fields = line.split_whitespace();
+                    Some((normalize_name(fields.next()?), fields.next().unwrap_or("").to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let size = f.size();
+    f.render_widget(Block::default().style(app.theme.base()), size);
+    if !fits(size) {
+        draw_too_small(f, app, size);
+        return;
+    }
+    let areas = Areas::new(size, app.layout, app.focused_pane(), app.zoom, status_lines(app));
+
+    let current_python_version = app.current_python_version.as_str();
+    // The version in use is starred, like in `pyenv versions`; the border and the
+    // cursor take four columns
+    let width = areas.versions.width.saturating_sub(4) as usize;
+    let version_items: Vec<ListItem> = app
+        .versions
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let active = clean_version(v) == current_python_version;
+            let style = if active {
+                app.theme.active()
+            } else if Some(i) == app.versions.selected() {
+                app.theme.selected()
+            } else {
+                Style::default()
+            };
+            let row = ellipsize(&format!("{}{}", if active { "* " } else { "  " }, v), width);
+            ListItem::new(highlight(app, &app.versions, row)).style(style)
+        })
+        .collect();
+
+    // Rows of the installed list, or why there are none yet
+    let (package_items, packages_title) = match (app.selected_inventory(), app.selected_package_error()) {
+        (Some(inventory), _) => {
+            let width = areas.packages.width.saturating_sub(4) as usize;
+            let rows = app.installed.items.iter().map(|p| ListItem::new(highlight(app, &app.installed, fit_package(p, width)))).collect();
+            let title = format!("Packages ({}){}{}", inventory.freshness.label(), position(&app.installed), search_note(&app.installed));
+            (rows, title)
+        }
+        (None, Some(error)) => {
+            let width = areas.packages.width.saturating_sub(2) as usize;
+            (vec![ListItem::new(ellipsize(&format!("Failed to read packages: {}", error), width))], String::from("Packages"))
+        }
+        (None, None) if app.versions.is_empty() => (Vec::new(), String::from("Packages")),
+        (None, None) => (vec![ListItem::new("Loading...")], String::from("Packages")),
+    };
+
+    draw_tabs(f, app, areas.tabs);
+    draw_details(f, app, areas.details);
+
+    let versions_title = format!("Python Versions{}{}", position(&app.versions), search_note(&app.versions));
+    draw_list(f, &app.versions, version_items, versions_title, app.theme.accent(), true, areas.versions);
+    draw_list(f, &app.installed, package_items, packages_title, app.theme.accent(), app.packages_focused, areas.packages);
+
+    let width = areas.status.width.saturating_sub(2) as usize;
+    let mut status_text = Vec::new();
+    if let Some(error) = &app.error_message {
+        status_text.push(Spans::from(Span::styled(
+            ellipsize(&format!("Error: {} (Esc to dismiss)", error), width),
+            app.theme.error().add_modifier(Modifier::BOLD),
+        )));
+    }
+    status_text.push(Spans::from(ellipsize(&format!("Current Python Version: {}", current_python_version), width)));
+    status_text.push(Spans::from(ellipsize(&format!("Python Environment: {}", app.python_env), width)));
+    status_text.push(Spans::from(ellipsize(&format!("Virtual Environment: {}", app.virtual_env), width)));
+    let status_block = Paragraph::new(status_text)
+        .block(Block::default().title("Status").borders(Borders::ALL));
+    f.render_widget(status_block, areas.status);
+
+    if app.show_log {
+        draw_log(f, app, areas.panel);
+    }
+
+    if app.diff.is_some() {
+        draw_diff(f, app, areas.panel);
+    }
+
+    if app.show_available {
+        draw_available(f, app, areas.panel);
+    }
+
+    if app.show_pypi {
+        // Packages this version already has are marked, and dimmed
+        let installed = installed_packages(app);
+        let width = areas.panel.width.saturating_sub(4) as usize;
+        let pypi_items: Vec<ListItem> = app.pypi_packages.items.iter().enumerate().map(|(i, p)| {
+            let version = installed.get(&normalize_name(p));
+            let style = if Some(i) == app.pypi_packages.selected() {
+                app.theme.selected()
+            } else if version.is_some() {
+                app.theme.muted()
+            } else {
+                Style::default()
+            };
+            let row = match version {
+                Some(version) => ellipsize(&format!("{} (installed {})", p, version), width),
+                None => ellipsize(p, width),
+            };
+            ListItem::new(highlight(app, &app.pypi_packages, row)).style(style)
+        }).collect();
+
+        let pypi_title = format!("PyPI Packages{}{}", position(&app.pypi_packages), search_note(&app.pypi_packages));
+        draw_list(f, &app.pypi_packages, pypi_items, pypi_title, app.theme.accent(), true, areas.panel);
+    }
+
+    if app.command_line.is_some() {
+        draw_command_line(f, app, areas.footer);
+    } else if let Some(input) = app.searching {
+        f.render_widget(Paragraph::new(format!("/{}_", app.list(input.list).search.query)), areas.footer);
+    } else {
+        draw_footer(f, app, areas.footer);
+    }
+    for modal in &app.modals {
+        draw_modal(f, app, modal);
+    }
+    if app.show_help {
+        draw_help(f, app);
+    }
+    draw_toasts(f, app);
+}
+
+// Instead of the panes on a screen too small to lay them out; any resize redraws
+fn draw_too_small<B: Backend>(f: &mut Frame<B>, app: &App, size: Rect) {
+    let lines = vec![
+        Spans::from("Terminal too small"),
+        Spans::from(format!("{}x{}, needs {}x{}", size.width, size.height, MIN_WIDTH, MIN_HEIGHT)),
+    ];
+    let height = (lines.len() as u16).min(size.height);
+    let area = Rect::new(size.x, size.y + (size.height - height) / 2, size.width, height);
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center).style(app.theme.warning()), area);
+}
+
+// " 2/40" for the second of forty rows; nothing for an empty list
+fn position(list: &StatefulList<String>) -> String {
+    match list.selected() {
+        Some(selected) => format!(" {}/{}", selected + 1, list.len()),
+        None => String::new(),
+    }
+}
+
+// " /req 3 of 40" for a search matching three rows of forty, and " filtered" while
+// the rest are hidden; nothing without a query
+fn search_note(list: &StatefulList<String>) -> String {
+    if list.search.is_empty() {
+        return String::new();
+    }
+    let filtered = if list.search.filtering() { " filtered" } else { "" };
+    format!(" /{} {} of {}{}", list.search.query, list.matches().len(), list.len(), filtered)
+}
+
+// A row with the characters the list's query matched underlined. The row is
+// matched as drawn, so the marks line up whatever was added or cut off.
+fn highlight(app: &App, list: &StatefulList<String>, row: String) -> Spans<'static> {
+    let positions = match list.search.positions(&row) {
+        Some(positions) if !positions.is_empty() => positions,
+        _ => return Spans::from(row),
+    };
+    let matched = app.theme.accent().add_modifier(Modifier::UNDERLINED);
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in row.chars().enumerate() {
+        let is_match = positions.contains(&i);
+        if is_match != run_matched && !run.is_empty() {
+            let text = std::mem::take(&mut run);
+            spans.push(if run_matched { Span::styled(text, matched) } else { Span::raw(text) });
+        }
+        run_matched = is_match;
+        run.push(c);
+    }
+    spans.push(if run_matched { Span::styled(run, matched) } else { Span::raw(run) });
+    Spans::from(spans)
+}
+
+// A bordered list scrolled to keep its cursor in view, with a scrollbar on the
+// right border when it doesn't fit. `rows` has one row per item, or stands in for
+// an empty list (e.g. "Loading..."); only the rows shown and on screen are handed
+// to tui, so `StatefulList::item_at_row` agrees with what is drawn. The cursor is
+// only drawn while the keys move it.
+fn draw_list<B: Backend>(
+    f: &mut Frame<B>,
+    list: &StatefulList<String>,
+    rows: Vec<ListItem>,
+    title: String,
+    style: Style,
+    cursor: bool,
+    area: Rect,
+) {
+    let height = area.height.saturating_sub(2) as usize;
+    let offset = list.scroll(height);
+    let shown = list.shown();
+    let rows: Vec<ListItem> = if list.is_empty() {
+        rows.into_iter().take(height).collect()
+    } else {
+        let mut rows: Vec<Option<ListItem>> = rows.into_iter().map(Some).collect();
+        shown.iter().skip(offset).take(height).filter_map(|&i| rows.get_mut(i).and_then(Option::take)).collect()
+    };
+    let widget = List::new(rows)
+        .block(Block::default().title(title).borders(Borders::ALL).style(style))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    let mut state = ListState::default();
+    let row = list.selected().and_then(|selected| shown.iter().position(|&i| i == selected));
+    state.select(row.filter(|_| cursor).map(|row| row - offset));
+    f.render_stateful_widget(widget, area, &mut state);
+    f.render_widget(Scrollbar { len: shown.len(), offset }, area);
+}
+
+// Drawn over the right border of a list: a thumb sized and placed by how much of
+// the list is on screen
+struct Scrollbar {
+    len: usize,
+    offset: usize,
+}
+
+impl Widget for Scrollbar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let height = area.height.saturating_sub(2) as usize;
+        if area.width < 2 || height == 0 || self.len <= height {
+            return;
+        }
+        let thumb = (height * height / self.len).max(1);
+        let start = self.offset * (height - thumb) / (self.len - height);
+        let x = area.right() - 1;
+        for row in start..start + thumb {
+            buf.get_mut(x, area.y + 1 + row as u16).set_symbol("█");
+        }
+    }
+}
+
+// One line of key hints for the focused pane, help first so it survives being
+// cut off on narrow terminals
+fn draw_footer<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let mut hints = app.hints();
+    if let Some(help) = hints.iter().position(|hint| hint.action == Some(Action::Help)) {
+        let help = hints.remove(help);
+        hints.insert(0, help);
+    }
+    // A motion being typed, like vim's showcmd
+    let mut spans = Vec::new();
+    if app.navigator.is_pending() {
+        spans.push(Span::styled(app.navigator.typed().to_string(), app.theme.accent().add_modifier(Modifier::BOLD)));
+    }
+    for hint in hints {
+        if !spans.is_empty() {
+            spans.push(Span::raw("  "));
+        }
+        let description = match hint.action {
+            Some(Action::Help) => String::from("help"),
+            _ => hint.description.to_lowercase(),
+        };
+        spans.push(Span::styled(hint.keys, Style::default().add_modifier(Modifier::BOLD)));
+        spans.push(Span::raw(format!(" {}", description)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
+
+// The `:` line in place of the footer, with ambiguous completions just above it
+fn draw_command_line<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let line = match &app.command_line {
+        Some(line) => line,
+        None => return,
+    };
+    f.render_widget(Paragraph::new(format!(":{}_", line.input)), area);
+    if !line.completions.is_empty() && area.y > 0 {
+        let above = Rect::new(area.x, area.y - 1, area.width, 1);
+        let completions = Paragraph::new(line.completions.join("  ")).style(app.theme.muted().add_modifier(Modifier::REVERSED));
+        f.render_widget(Clear, above);
+        f.render_widget(completions, above);
+    }
+}
+
+// What pyenv can install, newest first, with the versions already installed dimmed
+fn draw_available<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let width = area.width.saturating_sub(4) as usize;
+    let installed: Vec<String> = app.versions.items.iter().map(|v| clean_version(v)).collect();
+    let items: Vec<ListItem> = match &app.available_error {
+        Some(error) if app.available.is_empty() => {
+            vec![ListItem::new(ellipsize(&format!("Failed to list versions: {}", error), width))]
+        }
+        _ if app.available.is_empty() => vec![ListItem::new("Loading...")],
+        _ => app.available.items.iter().enumerate().map(|(i, version)| {
+            let style = if Some(i) == app.available.selected() {
+                app.theme.selected()
+            } else if installed.contains(version) {
+                app.theme.muted()
+            } else {
+                Style::default()
+            };
+            let row = if installed.contains(version) { format!("{} (installed)", version) } else { version.clone() };
+            ListItem::new(highlight(app, &app.available, ellipsize(&row, width))).style(style)
+        }).collect(),
+    };
+    let title = format!("Available Versions{}{}", position(&app.available), search_note(&app.available));
+    f.render_widget(Clear, area);
+    draw_list(f, &app.available, items, title, Style::default(), app.focused_list() == ListId::Available, area);
+}
+
+// `:diff` output over the middle panel
+fn draw_diff<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let diff = match &app.diff {
+        Some(diff) => diff,
+        None => return,
+    };
+    let items: Vec<ListItem> = if diff.lines.is_empty() {
+        vec![ListItem::new("Same packages and versions")]
+    } else {
+        diff.lines
+            .iter()
+            .map(|line| {
+                let style = match line.chars().next() {
+                    Some('-') => app.theme.error(),
+                    Some('+') => app.theme.success(),
+                    _ => app.theme.warning(),
+                };
+                ListItem::new(line.clone()).style(style)
+            })
+            .collect()
+    };
+    let title = format!("Packages: {} -> {}", diff.left, diff.right);
+    f.render_widget(Clear, area);
+    f.render_widget(List::new(items).block(Block::default().title(title).borders(Borders::ALL)), area);
+}
+
+// A dialog's lines inside its border
+fn modal_lines(modal: &Modal, keymap: &Keymap) -> Vec<Spans<'static>> {
+    match modal {
+        Modal::Confirm { message, .. } | Modal::Progress { message, .. } => vec![Spans::from(message.clone())],
+        Modal::Choice { choices, selected, .. } => choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| {
+                let line = format!("{}. {}", Modal::choice_key(choice, i, keymap), choice.label);
+                if i == *selected {
+                    Spans::from(Span::styled(format!("> {}", line), Style::default().add_modifier(Modifier::BOLD)))
+                } else {
+                    Spans::from(format!("  {}", line))
+                }
+            })
+            .collect(),
+        Modal::Input { value, .. } => vec![Spans::from(format!("{}_", value))],
+    }
+}
+
+// Centered over the whole screen, sized to the dialog's text
+pub fn modal_area(size: Rect, modal: &Modal, keymap: &Keymap) -> Rect {
+    let lines = modal_lines(modal, keymap);
+    let content_width = lines.iter().map(Spans::width).chain([modal.title().chars().count()]).max().unwrap_or(0);
+    let width = (content_width as u16 + 4).max(30).min(size.width);
+    let height = (lines.len() as u16 + 2).min(size.height);
+    Rect::new(size.x + (size.width - width) / 2, size.y + (size.height - height) / 2, width, height)
+}
+
+fn draw_modal<B: Backend>(f: &mut Frame<B>, app: &App, modal: &Modal) {
+    let area = modal_area(f.size(), modal, &app.keymap);
+    let style = match modal {
+        Modal::Confirm { .. } => app.theme.error(),
+        Modal::Progress { .. } => app.theme.muted(),
+        Modal::Choice { .. } | Modal::Input { .. } => app.theme.accent(),
+    };
+    let block = Block::default().title(modal.title().to_string()).borders(Borders::ALL).style(style);
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(modal_lines(modal, &app.keymap)).block(block), area);
+}
+
+// Centered over the whole screen, sized to the help's lines and the cursor
+pub fn help_area(size: Rect, help: &StatefulList<String>) -> Rect {
+    let content_width = help.items.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let width = (content_width as u16 + 6).min(size.width);
+    let height = (help.len() as u16 + 2).min(size.height);
+    Rect::new(size.x + (size.width - width) / 2, size.y + (size.height - height) / 2, width, height)
+}
+
+// Every key that does something in the focused pane, scrolled like the lists
+// when there are more than the screen holds
+fn draw_help<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let keys_width = app.hints().iter().map(|hint| hint.keys.chars().count()).max().unwrap_or(0);
+    let lines: Vec<ListItem> = app
+        .help
+        .items
+        .iter()
+        .map(|line| {
+            let (keys, description) = line.split_at(line.char_indices().nth(keys_width).map_or(line.len(), |(i, _)| i));
+            ListItem::new(Spans::from(vec![
+                Span::styled(keys.to_string(), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(description.to_string()),
+            ]))
+        })
+        .collect();
+
+    let area = help_area(f.size(), &app.help);
+    let title = format!("Keys: {}{}", app.focus_name(), position(&app.help));
+    f.render_widget(Clear, area);
+    draw_list(f, &app.help, lines, title, Style::default(), true, area);
+}
+
+fn level_style(app: &App, level: Level) -> Style {
+    match level {
+        Level::ERROR => app.theme.error(),
+        Level::WARN => app.theme.warning(),
+        Level::INFO => app.theme.success(),
+        _ => app.theme.muted(),
+    }
+}
+
+// Recent log lines, newest at the bottom, filtered by the panel's level; the
+// list's rows are the same lines in the same order
+fn draw_log<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    // The border, the cursor, the time and the level come before the message
+    let width = area.width.saturating_sub(4 + 9 + 6) as usize;
+    let items: Vec<ListItem> = app
+        .visible_log_lines()
+        .map(|line| {
+            let message = ellipsize(&line.message, width);
+            let mut spans = vec![
+                Span::raw(format!("{} ", line.time)),
+                Span::styled(format!("{:<5} ", line.level), level_style(app, line.level)),
+            ];
+            // Matched against the message alone, so the marks line up with it
+            spans.extend(highlight(app, &app.log, message).0);
+            ListItem::new(Spans::from(spans))
+        })
+        .collect();
+
+    // The keys as bound, like the footer's hints
+    let title = format!(
+        "Log (level {} and above; {}: filter, {}: close){}{}",
+        app.log_filter.as_str().to_lowercase(),
+        app.keymap.describe_keys(Action::CycleLogFilter),
+        app.keymap.describe_keys(Action::ToggleLog),
+        position(&app.log),
+        search_note(&app.log)
+    );
+    f.render_widget(Clear, area);
+    draw_list(f, &app.log, items, title, Style::default(), app.focused_list() == ListId::Log, area);
+}
+
+// Stack live toasts in the top-right corner
+fn draw_toasts<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let size = f.size();
+    let mut y = size.y;
+    for toast in &app.toasts {
+        if y + 3 > size.bottom() {
+            break;
+        }
+        let width = (toast.message.chars().count() as u16 + 2).min(50).min(size.width);
+        let area = Rect::new(size.right() - width, y, width, 3);
+        let toast_block = Paragraph::new(toast.message.as_str())
+            .style(level_style(app, toast.level))
+            .block(Block::default().borders(Borders::ALL).style(level_style(app, toast.level)));
+        f.render_widget(Clear, area);
+        f.render_widget(toast_block, area);
+        y += 3;
+    }
+}
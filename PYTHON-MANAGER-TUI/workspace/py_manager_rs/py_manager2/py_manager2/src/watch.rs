@@ -0,0 +1,112 @@
+// Watches the files pyenv keeps its state in, so changes made from another
+// terminal (a `pyenv global`, a new install, a `pip install`) show up without polling.
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use py_manager_core::site_packages;
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+
+use crate::event::Event;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsChange {
+    // The pyenv `version` file or a local `.python-version` changed
+    ActiveVersion,
+    // Something was installed into or removed from `versions/`
+    Versions,
+    // A version's site-packages changed
+    SitePackages(String),
+}
+
+struct Route {
+    dir: PathBuf,
+    // Only react to this entry of `dir` rather than to anything in it
+    file_name: Option<&'static str>,
+    change: FsChange,
+}
+
+impl Route {
+    fn matches(&self, path: &Path) -> bool {
+        match self.file_name {
+            Some(name) => path.parent() == Some(self.dir.as_path()) && path.file_name() == Some(OsStr::new(name)),
+            None => path == self.dir || path.parent() == Some(self.dir.as_path()),
+        }
+    }
+}
+
+pub struct FsWatcher {
+    watcher: RecommendedWatcher,
+    routes: Arc<Mutex<Vec<Route>>>,
+}
+
+impl FsWatcher {
+    pub fn new(tx: UnboundedSender<Event>) -> notify::Result<FsWatcher> {
+        let routes: Arc<Mutex<Vec<Route>>> = Arc::new(Mutex::new(Vec::new()));
+        let callback_routes = Arc::clone(&routes);
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!(error = %e, "file watcher error");
+                    return;
+                }
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+
+            let mut changes: Vec<FsChange> = Vec::new();
+            for route in callback_routes.lock().unwrap().iter() {
+                if event.paths.iter().any(|path| route.matches(path)) && !changes.contains(&route.change) {
+                    changes.push(route.change.clone());
+                }
+            }
+            for change in changes {
+                let _ = tx.send(Event::FsChanged(change));
+            }
+        })?;
+
+        let mut fs_watcher = FsWatcher { watcher, routes };
+        if let Some(root) = site_packages::pyenv_root() {
+            fs_watcher.watch(root.clone(), Some("version"), FsChange::ActiveVersion);
+            fs_watcher.watch(root.join("versions"), None, FsChange::Versions);
+        }
+        if let Ok(cwd) = env::current_dir() {
+            fs_watcher.watch(cwd, Some(".python-version"), FsChange::ActiveVersion);
+        }
+        Ok(fs_watcher)
+    }
+
+    // Swap the watched site-packages directories for the given (version, directory) pairs
+    pub fn watch_site_packages(&mut self, dirs: Vec<(String, PathBuf)>) {
+        let stale: Vec<PathBuf> = {
+            let mut routes = self.routes.lock().unwrap();
+            let stale = routes
+                .iter()
+                .filter(|route| matches!(route.change, FsChange::SitePackages(_)))
+                .map(|route| route.dir.clone())
+                .collect();
+            routes.retain(|route| !matches!(route.change, FsChange::SitePackages(_)));
+            stale
+        };
+        for dir in stale {
+            let _ = self.watcher.unwatch(&dir);
+        }
+
+        for (version, dir) in dirs {
+            self.watch(dir, None, FsChange::SitePackages(version));
+        }
+    }
+
+    fn watch(&mut self, dir: PathBuf, file_name: Option<&'static str>, change: FsChange) {
+        if let Err(e) = self.watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            warn!(dir = %dir.display(), error = %e, "failed to watch");
+            return;
+        }
+        self.routes.lock().unwrap().push(Route { dir, file_name, change });
+    }
+}
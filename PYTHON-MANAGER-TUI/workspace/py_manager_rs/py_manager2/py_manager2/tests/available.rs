@@ -0,0 +1,73 @@
+// The versions pyenv can install: read once when the pane is first opened,
+// filtered by the config, and searched and closed like the other panel lists.
+
+mod common;
+
+use crossterm::event::KeyCode;
+
+use py_manager2::app::{App, ListId, Task};
+use py_manager2::config::Config;
+use py_manager2::event::Event;
+use py_manager_core::error::Error;
+
+use common::{press, sample_app, type_keys};
+
+fn listed(app: &mut App, versions: &[&str]) {
+    app.handle_event(Event::AvailableVersions(Ok(versions.iter().map(|v| v.to_string()).collect())));
+}
+
+#[test]
+fn the_pane_lists_installable_versions_without_the_hidden_ones() {
+    let mut app = sample_app();
+    let tasks = type_keys(&mut app, "i");
+    assert!(matches!(tasks.as_slice(), [Task::ListAvailable]));
+    assert_eq!(app.focused_list(), ListId::Available);
+    assert_eq!(app.focus_name(), "Available versions");
+
+    listed(&mut app, &["3.13.0", "Anaconda3-2024.02", "3.12.4", "3.11.7"]);
+    assert_eq!(app.available.items, vec!["3.13.0", "3.12.4", "3.11.7"]);
+    type_keys(&mut app, "G");
+    assert_eq!(app.available.selected(), Some(2));
+    // The versions themselves keep their cursor
+    assert_eq!(app.selected_version().map(String::as_str), Some("system"));
+
+    type_keys(&mut app, "/3.12");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.available.selected_item().map(String::as_str), Some("3.12.4"));
+    press(&mut app, KeyCode::Esc);
+    assert!(app.available.search.is_empty());
+    press(&mut app, KeyCode::Esc);
+    assert!(!app.show_available);
+    assert_eq!(app.focused_list(), ListId::Versions);
+
+    // Reopening shows what was read before
+    assert!(type_keys(&mut app, "i").is_empty());
+    assert_eq!(app.available.len(), 3);
+}
+
+#[test]
+fn the_config_decides_what_is_hidden() {
+    let config = Config::parse("[filters]\nhide_available = [\"3.11\"]\n").unwrap();
+    let mut app = sample_app().with_config(config);
+    type_keys(&mut app, "i");
+    listed(&mut app, &["3.12.4", "Anaconda3-2024.02", "3.11.7"]);
+    assert_eq!(app.available.items, vec!["3.12.4", "Anaconda3-2024.02"]);
+}
+
+#[test]
+fn a_failed_listing_is_read_again_on_the_next_open() {
+    let mut app = sample_app();
+    assert!(type_keys(&mut app, ":available").is_empty());
+    let tasks = press(&mut app, KeyCode::Enter);
+    assert!(tasks.iter().any(|task| matches!(task, Task::ListAvailable)));
+    assert!(app.show_available);
+
+    app.handle_event(Event::AvailableVersions(Err(Error::ToolNotFound { tool: "pyenv" })));
+    assert_eq!(app.available_error.as_deref(), Some("`pyenv` was not found on PATH"));
+    assert!(app.error_message.as_deref().unwrap().starts_with("Listing installable versions failed"));
+
+    type_keys(&mut app, "i");
+    assert!(!app.show_available);
+    assert!(matches!(type_keys(&mut app, "i").as_slice(), [Task::ListAvailable]));
+    assert_eq!(app.available_error, None);
+}
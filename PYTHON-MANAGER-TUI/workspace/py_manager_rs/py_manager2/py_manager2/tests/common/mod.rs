@@ -0,0 +1,33 @@
+// Helpers shared by the integration tests: an app with three pyenv versions and
+// no packages loaded yet, and ways to send it keys.
+
+#![allow(dead_code)]
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use tui::layout::Rect;
+
+use py_manager2::app::{App, Task};
+use py_manager2::event::Event;
+
+// system, 3.11.7 and 3.12.4 on an 80x24 screen, with 3.12.4 active
+pub fn sample_app() -> App {
+    let versions = vec!["system".to_string(), "3.11.7".to_string(), "3.12.4".to_string()];
+    let venv = "/home/me/.pyenv/versions/3.12.4".to_string();
+    let mut app = App::new(versions, HashMap::new(), "3.12.4".to_string(), "Pyenv".to_string(), venv);
+    app.screen = Rect::new(0, 0, 80, 24);
+    app
+}
+
+pub fn press(app: &mut App, code: KeyCode) -> Vec<Task> {
+    press_with(app, code, KeyModifiers::NONE)
+}
+
+pub fn press_with(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Vec<Task> {
+    app.handle_event(Event::Input(TermEvent::Key(KeyEvent::new(code, modifiers))))
+}
+
+// Each character as its own key press
+pub fn type_keys(app: &mut App, keys: &str) -> Vec<Task> {
+    keys.chars().flat_map(|c| press(app, KeyCode::Char(c))).collect()
+}
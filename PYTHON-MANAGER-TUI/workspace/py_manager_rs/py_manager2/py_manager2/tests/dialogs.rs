@@ -0,0 +1,80 @@
+// The dialog stack: what opens which dialog, that the one on top keeps every key
+// to itself, and that progress boxes close when their work reports back.
+
+mod common;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use py_manager2::app::{App, Task};
+use py_manager2::event::Event;
+use py_manager2::modal::{Job, Modal};
+
+use common::{press, press_with, sample_app};
+
+fn top(app: &App) -> Option<&str> {
+    app.modals.last().map(Modal::title)
+}
+
+#[test]
+fn installing_asks_first_and_shows_progress_until_done() {
+    let mut app = sample_app();
+    press(&mut app, KeyCode::Enter);
+    assert!(matches!(press(&mut app, KeyCode::Char('2')).as_slice(), [Task::SearchPyPI(query)] if query.is_empty()));
+    assert_eq!(top(&app), Some("Searching"));
+    app.handle_event(Event::SearchResults(Ok(vec!["requests".to_string()])));
+    assert!(app.modals.is_empty());
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(top(&app), Some("Install"));
+    assert!(press(&mut app, KeyCode::Char('n')).is_empty());
+    assert!(app.modals.is_empty());
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(press(&mut app, KeyCode::Char('y')), vec![Task::Install("requests".to_string())]);
+    assert!(matches!(app.modals.as_slice(), [Modal::Progress { job: Job::Install, .. }]));
+    app.handle_event(Event::InstallFinished { package: "requests".to_string(), result: Ok(()) });
+    assert!(app.modals.is_empty());
+    assert!(!app.show_pypi);
+}
+
+#[test]
+fn the_top_dialog_keeps_the_keys_to_itself() {
+    let mut app = sample_app();
+    // Outside the options a plain digit is a motion count, never a switch
+    assert!(press(&mut app, KeyCode::Char('1')).iter().all(|task| !matches!(task, Task::Switch { .. })));
+
+    press(&mut app, KeyCode::Enter);
+    for code in [KeyCode::Char('q'), KeyCode::Char(':'), KeyCode::Char('L'), KeyCode::Char('?')] {
+        assert!(press(&mut app, code).is_empty());
+    }
+    assert!(!app.should_quit && !app.show_log && !app.show_help && app.command_line.is_none());
+    assert_eq!(app.modals.len(), 1);
+
+    // Typing a query: j and k are letters, not cursor keys
+    press(&mut app, KeyCode::Char('2'));
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('a'));
+    for c in "jk".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    assert_eq!(press(&mut app, KeyCode::Enter), vec![Task::SearchPyPI("jk".to_string())]);
+
+    // Hiding the progress box leaves the search running; ctrl-c still quits
+    press(&mut app, KeyCode::Esc);
+    assert!(app.modals.is_empty());
+    press(&mut app, KeyCode::Char('a'));
+    assert_eq!(top(&app), Some("Search PyPI"));
+    press_with(&mut app, KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert!(app.should_quit);
+}
+
+#[test]
+fn signals_reach_the_app_even_with_a_dialog_open() {
+    let mut app = sample_app();
+    press(&mut app, KeyCode::Char(':'));
+    press(&mut app, KeyCode::Char('a'));
+    assert_eq!(app.handle_event(Event::Suspend), vec![Task::Suspend]);
+    assert!(!app.should_quit);
+    assert!(app.handle_event(Event::Quit).is_empty());
+    assert!(app.should_quit);
+}
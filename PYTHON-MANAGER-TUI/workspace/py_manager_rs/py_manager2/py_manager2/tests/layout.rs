@@ -0,0 +1,96 @@
+// Resizing, zooming and re-splitting panes from the keyboard, and the layout that
+// is handed back to be saved for the next session.
+
+mod common;
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyModifiers};
+use tui::layout::Rect;
+
+use py_manager2::app::{App, Task};
+use py_manager2::config::{Layout, Split};
+use py_manager2::event::Event;
+use py_manager2::ui::Pane;
+
+use common::{press, press_with, sample_app};
+
+fn toggle_log(app: &mut App) {
+    press_with(app, KeyCode::Char('l'), KeyModifiers::CONTROL);
+}
+
+fn saved(tasks: &[Task]) -> Option<Layout> {
+    tasks.iter().find_map(|task| match task {
+        Task::SaveLayout(layout) => Some(*layout),
+        _ => None,
+    })
+}
+
+#[test]
+fn the_focused_pane_grows_and_shrinks_within_limits() {
+    let mut app = sample_app();
+    assert_eq!(saved(&press(&mut app, KeyCode::Char('+'))).map(|layout| layout.versions), Some(55));
+    for _ in 0..20 {
+        press(&mut app, KeyCode::Char('-'));
+    }
+    assert_eq!(app.layout.versions, 10);
+    assert!(press(&mut app, KeyCode::Char('-')).is_empty());
+
+    // With the log open the panel is focused and trades height with the main area
+    toggle_log(&mut app);
+    assert_eq!(app.focused_pane(), Pane::Panel);
+    let layout = saved(&press(&mut app, KeyCode::Char('='))).unwrap();
+    assert_eq!((layout.main, layout.panel, layout.status), (65, 25, 10));
+    for _ in 0..20 {
+        press(&mut app, KeyCode::Char('+'));
+    }
+    assert_eq!((app.layout.main, app.layout.panel), (20, 70));
+}
+
+#[test]
+fn zoom_follows_focus_and_the_split_flips() {
+    let mut app = sample_app();
+    assert!(press(&mut app, KeyCode::Char('z')).is_empty());
+    assert_eq!(app.zoomed_pane(), Some(Pane::Versions));
+    toggle_log(&mut app);
+    assert_eq!(app.zoomed_pane(), Some(Pane::Panel));
+    press(&mut app, KeyCode::Char('z'));
+    assert_eq!(app.zoomed_pane(), None);
+
+    assert_eq!(saved(&press(&mut app, KeyCode::Char('|'))).map(|layout| layout.split), Some(Split::Vertical));
+}
+
+#[test]
+fn layout_reset_goes_back_to_the_config() {
+    let mut app = sample_app();
+    press(&mut app, KeyCode::Char('|'));
+    press(&mut app, KeyCode::Char('+'));
+    press(&mut app, KeyCode::Char('z'));
+    let tasks: Vec<Task> = ":layout reset\n"
+        .chars()
+        .flat_map(|c| press(&mut app, if c == '\n' { KeyCode::Enter } else { KeyCode::Char(c) }))
+        .collect();
+    assert_eq!(saved(&tasks), Some(Layout::default()));
+    assert!(!app.zoom);
+}
+
+#[test]
+fn narrow_screens_show_only_the_focused_pane() {
+    let mut app = sample_app();
+    app.handle_event(Event::Input(TermEvent::Resize(50, 20)));
+    let areas = app.areas();
+    assert_eq!(areas.versions, Rect::default());
+    assert_eq!((areas.packages.width, areas.panel.height), (50, 0));
+    // The status box keeps its three lines however small its share, and grows by
+    // one while it shows an error
+    assert_eq!(areas.status.height, 5);
+    app.error_message = Some(String::from("Listing Python versions failed"));
+    assert_eq!(app.areas().status.height, 6);
+    app.error_message = None;
+
+    toggle_log(&mut app);
+    let areas = app.areas();
+    assert_eq!((areas.main.height, areas.panel.height), (0, 13));
+
+    app.handle_event(Event::Input(TermEvent::Resize(120, 40)));
+    let areas = app.areas();
+    assert!(areas.versions.width > 0 && areas.main.height > areas.panel.height);
+}
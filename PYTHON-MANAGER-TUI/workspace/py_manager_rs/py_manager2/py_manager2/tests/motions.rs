@@ -0,0 +1,112 @@
+// Vim motions in the version and package lists: counts, gg/G, half pages, the
+// screen motions and marks.
+
+mod common;
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyEvent, KeyModifiers};
+
+use py_manager2::app::{App, Task};
+use py_manager2::event::Event;
+use py_manager_core::cache::{Freshness, Inventory};
+
+use common::{press, sample_app, type_keys};
+
+fn open_packages(app: &mut App, count: usize) {
+    app.show_pypi = true;
+    app.pypi_packages.set_items((1..=count).map(|i| format!("package-{:02}", i)).collect());
+}
+
+#[test]
+fn counts_and_jumps_move_through_the_packages() {
+    let mut app = sample_app();
+    open_packages(&mut app, 60);
+    type_keys(&mut app, "10j");
+    assert_eq!(app.pypi_packages.selected(), Some(10));
+    type_keys(&mut app, "G");
+    assert_eq!(app.pypi_packages.selected(), Some(59));
+    type_keys(&mut app, "gg");
+    assert_eq!(app.pypi_packages.selected(), Some(0));
+    type_keys(&mut app, "25G");
+    assert_eq!(app.pypi_packages.selected(), Some(24));
+    type_keys(&mut app, "3k");
+    assert_eq!(app.pypi_packages.selected(), Some(21));
+
+    let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+    app.handle_event(Event::Input(TermEvent::Key(ctrl_d)));
+    assert!(app.pypi_packages.selected() > Some(21));
+}
+
+#[test]
+fn the_typed_count_is_shown_until_the_motion() {
+    let mut app = sample_app();
+    open_packages(&mut app, 20);
+    assert!(type_keys(&mut app, "1").is_empty());
+    type_keys(&mut app, "2");
+    assert_eq!(app.navigator.typed(), "12");
+    type_keys(&mut app, "j");
+    assert!(!app.navigator.is_pending());
+    assert_eq!(app.pypi_packages.selected(), Some(12));
+}
+
+#[test]
+fn moving_through_versions_loads_their_packages() {
+    let mut app = sample_app();
+    let tasks = type_keys(&mut app, "G");
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.12.4"));
+    assert!(matches!(tasks.as_slice(), [Task::RefreshPackages { version, .. }] if version == "3.12.4"));
+    assert!(type_keys(&mut app, "G").is_empty());
+}
+
+#[test]
+fn marks_come_back_to_their_row() {
+    let mut app = sample_app();
+    open_packages(&mut app, 30);
+    type_keys(&mut app, "7jmaG'a");
+    assert_eq!(app.pypi_packages.selected(), Some(7));
+
+    type_keys(&mut app, "'b");
+    assert_eq!(app.error_message.as_deref(), Some("Jumping to a mark failed: mark `b` isn't set in this list"));
+    // Marks belong to their list
+    app.show_pypi = false;
+    type_keys(&mut app, "'a");
+    assert_eq!(app.selected_version().map(String::as_str), Some("system"));
+}
+
+#[test]
+fn tab_moves_the_cursor_keys_to_the_versions_packages() {
+    let mut app = sample_app();
+    app.handle_event(Event::PackagesLoaded {
+        version: "system".to_string(),
+        inventory: Inventory {
+            packages: (1..=30).map(|i| format!("package-{:02} 1.0", i)).collect(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    });
+    assert_eq!(app.installed.len(), 30);
+
+    press(&mut app, KeyCode::Tab);
+    assert!(app.packages_focused);
+    assert!(type_keys(&mut app, "5j").is_empty());
+    assert_eq!(app.installed.selected(), Some(5));
+    assert_eq!(app.selected_version().map(String::as_str), Some("system"));
+    type_keys(&mut app, "G");
+    assert_eq!(app.installed.selected(), Some(29));
+
+    // Reading the same version again keeps the cursor on its package
+    app.handle_event(Event::PackagesLoaded {
+        version: "system".to_string(),
+        inventory: Inventory {
+            packages: (2..=30).map(|i| format!("package-{:02} 1.0", i)).collect(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    });
+    assert_eq!(app.installed.selected_item().map(String::as_str), Some("package-30 1.0"));
+
+    press(&mut app, KeyCode::Esc);
+    assert!(!app.packages_focused);
+    type_keys(&mut app, "j");
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.11.7"));
+    assert!(app.installed.is_empty());
+}
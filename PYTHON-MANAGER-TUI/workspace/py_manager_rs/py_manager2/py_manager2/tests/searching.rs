@@ -0,0 +1,149 @@
+// The `/` search in every list: the cursor follows the first match as the query
+// is typed, n/N step between matches, F hides the rest, and Esc takes it back.
+
+mod common;
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use tracing::Level;
+
+use py_manager2::app::{App, ListId, Task};
+use py_manager2::event::Event;
+use py_manager2::logging::LogLine;
+use py_manager_core::cache::{Freshness, Inventory};
+
+use common::{press, press_with, sample_app, type_keys};
+
+fn load_packages(app: &mut App, version: &str, packages: &[&str]) {
+    app.handle_event(Event::PackagesLoaded {
+        version: version.to_string(),
+        inventory: Inventory {
+            packages: packages.iter().map(|p| p.to_string()).collect(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    });
+}
+
+fn selected(app: &App, list: ListId) -> Option<&str> {
+    app.list(list).selected_item().map(String::as_str)
+}
+
+#[test]
+fn typing_a_query_follows_the_first_match_and_reads_its_packages() {
+    let mut app = sample_app();
+    press(&mut app, KeyCode::Char('/'));
+    assert_eq!(app.searching.map(|input| input.list), Some(ListId::Versions));
+    type_keys(&mut app, "3");
+    assert_eq!(selected(&app, ListId::Versions), Some("3.11.7"));
+    let tasks = type_keys(&mut app, "12");
+    assert_eq!(selected(&app, ListId::Versions), Some("3.12.4"));
+    assert!(matches!(tasks.as_slice(), [Task::RefreshPackages { version, .. }] if version == "3.12.4"));
+
+    // Keys go to the query until it's kept, so `j` isn't a motion here
+    type_keys(&mut app, "j");
+    assert_eq!(app.versions.search.query, "312j");
+    assert_eq!(selected(&app, ListId::Versions), Some("system"));
+    press(&mut app, KeyCode::Backspace);
+    assert_eq!(selected(&app, ListId::Versions), Some("3.12.4"));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.searching.is_none());
+    assert_eq!(app.versions.search.query, "312");
+}
+
+#[test]
+fn n_and_shift_n_wrap_around_the_matches() {
+    let mut app = sample_app();
+    type_keys(&mut app, "/3.1");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(selected(&app, ListId::Versions), Some("3.11.7"));
+    type_keys(&mut app, "n");
+    assert_eq!(selected(&app, ListId::Versions), Some("3.12.4"));
+    type_keys(&mut app, "n");
+    assert_eq!(selected(&app, ListId::Versions), Some("3.11.7"));
+    type_keys(&mut app, "N");
+    assert_eq!(selected(&app, ListId::Versions), Some("3.12.4"));
+}
+
+#[test]
+fn escape_while_typing_goes_back_to_where_the_cursor_was() {
+    let mut app = sample_app();
+    type_keys(&mut app, "j/3.12");
+    assert_eq!(selected(&app, ListId::Versions), Some("3.12.4"));
+    press(&mut app, KeyCode::Esc);
+    assert!(app.searching.is_none());
+    assert!(app.versions.search.is_empty());
+    assert_eq!(selected(&app, ListId::Versions), Some("3.11.7"));
+}
+
+#[test]
+fn the_filter_hides_packages_that_dont_match() {
+    let mut app = sample_app();
+    load_packages(&mut app, "system", &["pip 24.0", "requests 2.32.3", "six 1.16.0", "urllib3 2.2.2", "idna 3.7"]);
+    press(&mut app, KeyCode::Tab);
+    type_keys(&mut app, "/i");
+    press(&mut app, KeyCode::Enter);
+    // pip, six, urllib3 and idna
+    assert_eq!(app.installed.matches().len(), 4);
+    type_keys(&mut app, "F");
+    assert!(app.installed.search.filtering());
+    assert_eq!(app.installed.shown().len(), 4);
+    type_keys(&mut app, "G");
+    assert_eq!(selected(&app, ListId::Installed), Some("idna 3.7"));
+    type_keys(&mut app, "k");
+    assert_eq!(selected(&app, ListId::Installed), Some("urllib3 2.2.2"));
+    assert!(app.hints().iter().any(|hint| hint.description == "Clear the search"));
+
+    // The first Esc clears the search, the next gives the keys back to the versions
+    press(&mut app, KeyCode::Esc);
+    assert!(app.installed.search.is_empty());
+    assert_eq!(app.installed.shown().len(), 5);
+    assert!(app.packages_focused);
+    press(&mut app, KeyCode::Esc);
+    assert!(!app.packages_focused);
+}
+
+#[test]
+fn clicks_land_on_filtered_rows() {
+    let mut app = sample_app();
+    app.show_pypi = true;
+    app.pypi_packages.set_items(["flask", "requests", "django", "requests-toolbelt"].map(String::from).to_vec());
+    type_keys(&mut app, "/req");
+    press(&mut app, KeyCode::Enter);
+    // Zoomed, so the panel has room for both matches
+    type_keys(&mut app, "Fz");
+    let panel = app.areas().panel;
+    let click = MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: panel.x + 2,
+        row: panel.y + 2,
+        modifiers: KeyModifiers::NONE,
+    };
+    app.handle_event(Event::Input(TermEvent::Mouse(click)));
+    assert_eq!(selected(&app, ListId::PyPI), Some("requests-toolbelt"));
+    // A new search result keeps the query and its filter
+    app.pypi_packages.set_items(["requests-mock", "httpx"].map(String::from).to_vec());
+    assert_eq!(app.pypi_packages.shown(), vec![0]);
+}
+
+#[test]
+fn the_log_is_searched_line_by_line() {
+    let mut app = sample_app();
+    for (level, message) in [(Level::INFO, "starting"), (Level::WARN, "failed to watch /tmp/gone"), (Level::INFO, "exiting")] {
+        app.handle_event(Event::Log(LogLine { time: "12:00:00".to_string(), level, message: message.to_string() }));
+    }
+    press_with(&mut app, KeyCode::Char('l'), KeyModifiers::CONTROL);
+    assert_eq!(app.focused_list(), ListId::Log);
+    // The cursor starts on the newest line and stays there as more arrive
+    assert!(selected(&app, ListId::Log).unwrap().ends_with("exiting"));
+    app.handle_event(Event::Log(LogLine { time: "12:00:01".to_string(), level: Level::INFO, message: "tick".to_string() }));
+    assert!(selected(&app, ListId::Log).unwrap().ends_with("tick"));
+
+    type_keys(&mut app, "/watch");
+    press(&mut app, KeyCode::Enter);
+    assert!(selected(&app, ListId::Log).unwrap().ends_with("failed to watch /tmp/gone"));
+    // Anywhere but the newest line the cursor stays put as lines arrive
+    app.handle_event(Event::Log(LogLine { time: "12:00:02".to_string(), level: Level::INFO, message: "tock".to_string() }));
+    assert!(selected(&app, ListId::Log).unwrap().ends_with("failed to watch /tmp/gone"));
+    type_keys(&mut app, "F");
+    assert_eq!(app.log.shown().len(), 1);
+}
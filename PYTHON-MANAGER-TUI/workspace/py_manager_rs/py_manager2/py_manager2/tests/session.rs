@@ -0,0 +1,150 @@
+// Saving where a session left off and putting it back on the next start.
+
+mod common;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use tracing::Level;
+
+use py_manager2::app::{ListId, Task};
+use py_manager2::event::Event;
+use py_manager2::session::{SavedSearch, Session};
+use py_manager2::ui::Pane;
+
+use py_manager_core::cache::{Freshness, Inventory};
+
+use common::{press, press_with, sample_app, type_keys};
+
+#[test]
+fn a_saved_session_comes_back_on_a_fresh_app() {
+    let mut app = sample_app();
+    // Pin 3.12.4 to the front, then look at 3.11.7 with the log filtered to warnings
+    press_with(&mut app, KeyCode::Char('3'), KeyModifiers::ALT);
+    press(&mut app, KeyCode::Char('p'));
+    press_with(&mut app, KeyCode::Char('3'), KeyModifiers::ALT);
+    press_with(&mut app, KeyCode::Char('l'), KeyModifiers::CONTROL);
+    for _ in 0..3 {
+        press(&mut app, KeyCode::Char('f'));
+    }
+    let session = app.session();
+    assert_eq!(session.tabs, ["3.12.4", "system", "3.11.7"]);
+    assert_eq!(session.version.as_deref(), Some("3.11.7"));
+    assert_eq!(session.log_filter, "warn");
+
+    let mut restored = sample_app();
+    assert!(restored.restore(session.clone()).is_empty());
+    assert_eq!(restored.session(), session);
+    assert_eq!(restored.selected_version().map(String::as_str), Some("3.11.7"));
+    assert_eq!(restored.log_filter, Level::WARN);
+    assert_eq!(restored.focused_pane(), Pane::Panel);
+}
+
+#[test]
+fn the_pypi_search_runs_again_and_reselects_its_package() {
+    let session = Session {
+        show_pypi: true,
+        query: String::from("requests"),
+        package: Some(String::from("requests-toolbelt")),
+        ..Session::default()
+    };
+    let mut app = sample_app();
+    let tasks = app.restore(session);
+    assert!(tasks.contains(&Task::SearchPyPI(String::from("requests"))), "{:?}", tasks);
+    assert!(app.show_pypi);
+
+    let results = ["requests", "requests-oauthlib", "requests-toolbelt"].map(String::from).to_vec();
+    app.handle_event(Event::SearchResults(Ok(results.clone())));
+    assert_eq!(app.pypi_packages.selected_item().map(String::as_str), Some("requests-toolbelt"));
+    assert_eq!(app.session().query, "requests");
+
+    // Later searches start at the top as usual
+    app.handle_event(Event::SearchResults(Ok(results)));
+    assert_eq!(app.pypi_packages.selected(), Some(0));
+}
+
+#[test]
+fn versions_that_are_gone_are_skipped() {
+    let session = Session {
+        tabs: vec![String::from("3.10.0"), String::from("3.11.7")],
+        version: Some(String::from("3.10.0")),
+        ..Session::default()
+    };
+    let mut app = sample_app();
+    app.restore(session);
+    assert_eq!(app.versions.items[0], "3.11.7");
+    assert_eq!(app.versions.selected(), Some(0));
+}
+
+#[test]
+fn each_lists_search_and_filter_come_back() {
+    let mut app = sample_app();
+    type_keys(&mut app, "/3.1");
+    press(&mut app, KeyCode::Enter);
+    type_keys(&mut app, "F");
+    press(&mut app, KeyCode::Tab);
+    type_keys(&mut app, "/req");
+    press(&mut app, KeyCode::Enter);
+    let session = app.session();
+    assert_eq!(session.searches.len(), 2);
+    assert_eq!(session.searches["versions"], SavedSearch { query: String::from("3.1"), filter: true });
+    assert_eq!(session.searches["installed"], SavedSearch { query: String::from("req"), filter: false });
+
+    let mut restored = sample_app();
+    restored.restore(session.clone());
+    assert_eq!(restored.session().searches, session.searches);
+    assert_eq!(restored.versions.shown(), [1, 2]);
+    assert_eq!(restored.selected_version().map(String::as_str), Some("3.11.7"));
+
+    // Packages read after the restore are matched against the saved query
+    restored.handle_event(Event::PackagesLoaded {
+        version: String::from("3.11.7"),
+        inventory: Inventory {
+            packages: ["pip 24.0", "requests 2.32.3"].map(String::from).to_vec(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    });
+    assert_eq!(restored.list(ListId::Installed).matches(), [1]);
+    // The page's packages still have the keys
+    assert_eq!(restored.focused_list(), ListId::Installed);
+    type_keys(&mut restored, "n");
+    assert_eq!(restored.installed.selected_item().map(String::as_str), Some("requests 2.32.3"));
+}
+
+#[test]
+fn the_focused_package_and_the_available_versions_come_back() {
+    let mut app = sample_app();
+    app.handle_event(Event::PackagesLoaded {
+        version: String::from("system"),
+        inventory: Inventory {
+            packages: ["pip 24.0", "requests 2.32.3", "six 1.16.0"].map(String::from).to_vec(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    });
+    press(&mut app, KeyCode::Tab);
+    type_keys(&mut app, "G");
+    type_keys(&mut app, "i");
+    let session = app.session();
+    assert_eq!(session.installed_package.as_deref(), Some("six"));
+    assert!(session.packages_focused);
+    assert!(session.show_available);
+
+    let mut restored = sample_app();
+    let tasks = restored.restore(session);
+    assert!(tasks.contains(&Task::ListAvailable), "{:?}", tasks);
+    assert_eq!(restored.focused_list(), ListId::Available);
+    assert!(restored.packages_focused);
+
+    // The package is selected once it is listed, whatever its version by then
+    restored.handle_event(Event::PackagesLoaded {
+        version: String::from("system"),
+        inventory: Inventory {
+            packages: ["pip 24.0", "six 1.17.0"].map(String::from).to_vec(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    });
+    assert_eq!(restored.installed.selected_item().map(String::as_str), Some("six 1.17.0"));
+    press(&mut restored, KeyCode::Esc);
+    assert_eq!(restored.focused_list(), ListId::Installed);
+}
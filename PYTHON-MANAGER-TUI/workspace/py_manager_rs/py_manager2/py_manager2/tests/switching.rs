@@ -0,0 +1,267 @@
+// Drives the version-switching flow end to end against a scripted pyenv: the key
+// presses that pick a version, the `pyenv global` call, and the app following the
+// active version that pyenv reports back.
+
+mod common;
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::HashMap;
+
+use py_manager2::app::{App, Task};
+use py_manager2::config::Config;
+use py_manager2::event::Event;
+use py_manager2::modal::Modal;
+use py_manager2::ui;
+use py_manager_core::operations::Scope;
+use py_manager_core::process::ScriptedRunner;
+use py_manager_core::{discovery, operations};
+use tui::layout::Rect;
+
+use common::press;
+
+const PYENV_VERSIONS: &str = "  system\n* 3.11.7 (set by /home/me/.pyenv/version)\n  3.12.4\n";
+
+async fn start(runner: &ScriptedRunner) -> App {
+    let versions = discovery::get_python_versions(runner).await.unwrap();
+    let current = discovery::get_current_python_version(runner).await.unwrap();
+    App::new(versions, HashMap::new(), current, "Pyenv".to_string(), "3.11)".to_string())
+}
+
+// Whatever the app asked to switch to, the way the binary carries it out
+async fn perform_switch(runner: &ScriptedRunner, tasks: Vec<Task>) -> Vec<Event> {
+    let (version, scope) = tasks
+        .into_iter()
+        .find_map(|task| match task {
+            Task::Switch { version, scope } => Some((version, scope)),
+            _ => None,
+        })
+        .expect("no switch was requested");
+    let result = operations::switch_version(runner, &version, scope).await;
+    if result.is_err() {
+        return vec![Event::SwitchFinished { version, scope, result }];
+    }
+    vec![
+        Event::SwitchFinished { version, scope, result },
+        Event::CurrentVersion(discovery::get_current_python_version(runner).await),
+    ]
+}
+
+fn global_switch(version: &str) -> Task {
+    Task::Switch { version: version.to_string(), scope: Scope::Global }
+}
+
+fn options_open(app: &App) -> bool {
+    matches!(app.modals.last(), Some(Modal::Choice { .. }))
+}
+
+fn type_text(app: &mut App, text: &str) -> Vec<Task> {
+    text.chars().flat_map(|c| press(app, KeyCode::Char(c))).collect()
+}
+
+#[tokio::test]
+async fn switching_runs_pyenv_global_and_follows_the_new_version() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.11.7\n")
+        .ok("pyenv version-name", "3.12.4\n")
+        .ok("pyenv global 3.12.4", "");
+    let mut app = start(&runner).await;
+    assert_eq!(app.current_python_version, "3.11.7");
+
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.12.4"));
+
+    press(&mut app, KeyCode::Enter);
+    assert!(options_open(&app));
+    let tasks = press(&mut app, KeyCode::Char('1'));
+    assert!(app.modals.is_empty());
+    assert!(tasks.contains(&global_switch("3.12.4")));
+
+    let mut followups = Vec::new();
+    for event in perform_switch(&runner, tasks).await {
+        followups.extend(app.handle_event(event));
+    }
+    // The status box is re-read off the UI loop for the new interpreter
+    assert!(followups.contains(&Task::RefreshStatus), "{:?}", followups);
+    app.handle_event(Event::StatusRead { python_env: "Pyenv".to_string(), virtual_env: "/home/me/.venv".to_string() });
+    assert_eq!(app.virtual_env, "/home/me/.venv");
+
+    assert_eq!(app.current_python_version, "3.12.4");
+    assert_eq!(app.versions.selected(), Some(2));
+    assert!(app.error_message.is_none());
+    let toasts: Vec<&str> = app.toasts.iter().map(|toast| toast.message.as_str()).collect();
+    assert_eq!(toasts, vec!["Switched to 3.12.4"]);
+    assert_eq!(
+        runner.command_lines(),
+        vec!["pyenv versions", "pyenv version-name", "pyenv global 3.12.4", "pyenv version-name"]
+    );
+}
+
+#[tokio::test]
+async fn switching_to_an_annotated_version_passes_the_bare_name() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.12.4\n")
+        .ok("pyenv global 3.11.7", "");
+    let mut app = start(&runner).await;
+
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Enter);
+    let tasks = press(&mut app, KeyCode::Char('1'));
+    assert!(tasks.contains(&global_switch("3.11.7")));
+}
+
+#[tokio::test]
+async fn a_failed_switch_is_reported_and_keeps_the_current_version() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.11.7\n")
+        .fail("pyenv global 3.12.4", 1, "pyenv: cannot write to /home/me/.pyenv/version\n");
+    let mut app = start(&runner).await;
+
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Enter);
+    let tasks = press(&mut app, KeyCode::Char('1'));
+
+    for event in perform_switch(&runner, tasks).await {
+        app.handle_event(event);
+    }
+
+    assert_eq!(app.current_python_version, "3.11.7");
+    assert!(app.toasts.is_empty());
+    let message = app.error_message.as_deref().unwrap_or_default();
+    assert!(message.starts_with("Switching to 3.12.4 failed"), "{}", message);
+    assert!(message.ends_with("cannot write to /home/me/.pyenv/version"), "{}", message);
+}
+
+#[tokio::test]
+async fn starting_without_pyenv_is_an_error_not_a_panic() {
+    let runner = ScriptedRunner::new().missing("pyenv");
+    let err = discovery::get_python_versions(&runner).await.unwrap_err();
+    assert_eq!(err.to_string(), "`pyenv` was not found on PATH");
+
+    let mut app = App::new(Vec::new(), HashMap::new(), String::new(), "None".to_string(), "Unknown".to_string());
+    assert!(press(&mut app, KeyCode::Char('j')).is_empty());
+    press(&mut app, KeyCode::Enter);
+    assert!(press(&mut app, KeyCode::Char('1')).is_empty());
+}
+
+#[tokio::test]
+async fn rebound_keys_drive_the_same_flow() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.11.7\n");
+    let config = Config::parse("[keys]\ndown = [\"t\"]\nswitch-version = [\"space\"]\n").unwrap();
+    let mut app = start(&runner).await.with_config(config);
+
+    press(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.versions.selected(), Some(0));
+    press(&mut app, KeyCode::Char('t'));
+    press(&mut app, KeyCode::Char('t'));
+    press(&mut app, KeyCode::Enter);
+    let switch = app.hints().into_iter().find(|hint| hint.description == "Switch to this version").unwrap();
+    assert_eq!(switch.keys, "space");
+    let tasks = press(&mut app, KeyCode::Char(' '));
+    assert!(tasks.contains(&global_switch("3.12.4")));
+}
+
+#[tokio::test]
+async fn the_command_line_switches_locally_by_version_prefix() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.11.7\n")
+        .ok("pyenv version-name", "3.12.4\n")
+        .ok("pyenv local 3.12.4", "");
+    let mut app = start(&runner).await;
+
+    press(&mut app, KeyCode::Char(':'));
+    type_text(&mut app, "sw");
+    press(&mut app, KeyCode::Tab);
+    type_text(&mut app, "3.12 --local");
+    assert_eq!(app.command_line.as_ref().map(|line| line.input.as_str()), Some("switch 3.12 --local"));
+    let tasks = press(&mut app, KeyCode::Enter);
+    assert!(app.command_line.is_none());
+    assert!(tasks.contains(&Task::Switch { version: "3.12.4".to_string(), scope: Scope::Local }));
+    assert!(tasks.contains(&Task::SaveHistory(vec!["switch 3.12 --local".to_string()])));
+
+    for event in perform_switch(&runner, tasks).await {
+        app.handle_event(event);
+    }
+    assert_eq!(app.current_python_version, "3.12.4");
+    let toasts: Vec<&str> = app.toasts.iter().map(|toast| toast.message.as_str()).collect();
+    assert_eq!(toasts, vec!["Switched to 3.12.4 in this directory"]);
+
+    // The line comes back from the history
+    press(&mut app, KeyCode::Char(':'));
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.command_line.as_ref().map(|line| line.input.as_str()), Some("switch 3.12 --local"));
+}
+
+#[tokio::test]
+async fn ambiguous_versions_are_reported_by_the_command_line() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.11.7\n");
+    let mut app = start(&runner).await;
+
+    press(&mut app, KeyCode::Char(':'));
+    type_text(&mut app, "switch 3.1");
+    let tasks = press(&mut app, KeyCode::Enter);
+    assert_eq!(tasks.len(), 1, "only the history is saved: {:?}", tasks);
+    assert_eq!(
+        app.error_message.as_deref(),
+        Some(":switch 3.1: `3.1` matches several versions: 3.11.7, 3.12.4")
+    );
+}
+
+fn click(app: &mut App, kind: MouseEventKind, column: u16, row: u16) -> Vec<Task> {
+    app.handle_event(Event::Input(TermEvent::Mouse(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE })))
+}
+
+#[tokio::test]
+async fn clicking_a_version_and_its_switch_option_switches() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.11.7\n");
+    let mut app = start(&runner).await;
+    app.screen = Rect::new(0, 0, 80, 24);
+    let areas = app.areas();
+    let left = MouseEventKind::Down(MouseButton::Left);
+
+    // Third row inside the version list's border
+    click(&mut app, left, areas.versions.x + 2, areas.versions.y + 3);
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.12.4"));
+    assert!(!options_open(&app));
+    click(&mut app, left, areas.versions.x + 2, areas.versions.y + 3);
+    assert!(options_open(&app));
+
+    // Clicks outside the dialog go nowhere; its first line is the switch option
+    assert!(click(&mut app, left, areas.versions.x + 2, areas.versions.y + 1).is_empty());
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.12.4"));
+    let dialog = ui::modal_area(app.screen, app.modals.last().unwrap(), &app.keymap);
+    let tasks = click(&mut app, left, dialog.x + 4, dialog.y + 1);
+    assert!(app.modals.is_empty());
+    assert!(tasks.contains(&global_switch("3.12.4")));
+}
+
+#[tokio::test]
+async fn the_wheel_moves_through_the_versions_under_the_pointer() {
+    let runner = ScriptedRunner::new()
+        .ok("pyenv versions", PYENV_VERSIONS)
+        .ok("pyenv version-name", "3.11.7\n");
+    let mut app = start(&runner).await;
+    app.handle_event(Event::Input(TermEvent::Resize(80, 24)));
+    let areas = app.areas();
+
+    click(&mut app, MouseEventKind::ScrollDown, areas.versions.x + 2, areas.versions.y + 2);
+    click(&mut app, MouseEventKind::ScrollDown, areas.versions.x + 2, areas.versions.y + 2);
+    click(&mut app, MouseEventKind::ScrollDown, areas.versions.x + 2, areas.versions.y + 2);
+    assert_eq!(app.versions.selected(), Some(2));
+    // Over the packages nothing moves
+    click(&mut app, MouseEventKind::ScrollUp, areas.packages.x + 2, areas.packages.y + 2);
+    assert_eq!(app.versions.selected(), Some(2));
+    click(&mut app, MouseEventKind::ScrollUp, areas.versions.x + 2, areas.versions.y + 2);
+    assert_eq!(app.versions.selected(), Some(1));
+}
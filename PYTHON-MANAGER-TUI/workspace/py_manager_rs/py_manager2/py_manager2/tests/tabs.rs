@@ -0,0 +1,147 @@
+// The per-version tab bar: alt and a number and h/l to move between pages, and tabs
+// that can be reordered and pinned without a rediscovery undoing it, and each page's
+// packages, scrolled by the wheel or, once focused, the keys.
+
+mod common;
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::HashMap;
+
+use py_manager2::app::{App, Task};
+use py_manager2::event::Event;
+use py_manager2::ui;
+use py_manager_core::cache::{Freshness, Inventory};
+
+use common::{press, press_with, sample_app};
+
+fn jump_to_tab(app: &mut App, digit: char) -> Vec<Task> {
+    press_with(app, KeyCode::Char(digit), KeyModifiers::ALT)
+}
+
+fn tabs(app: &App) -> Vec<&str> {
+    app.versions.items.iter().map(String::as_str).collect()
+}
+
+#[test]
+fn number_keys_and_h_l_move_between_tabs() {
+    let mut app = sample_app();
+    let tasks = jump_to_tab(&mut app, '3');
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.12.4"));
+    assert!(matches!(tasks.as_slice(), [Task::RefreshPackages { version, .. }] if version == "3.12.4"));
+
+    // No ninth tab to jump to
+    assert!(jump_to_tab(&mut app, '9').is_empty());
+    press(&mut app, KeyCode::Char('l'));
+    assert_eq!(app.selected_version().map(String::as_str), Some("system"));
+    press(&mut app, KeyCode::Char('h'));
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.12.4"));
+
+    // In the options popup the numbers pick an option instead
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('2'));
+    assert!(app.show_pypi);
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.12.4"));
+}
+
+#[test]
+fn pinned_tabs_stay_in_front_of_the_rest() {
+    let mut app = sample_app();
+    jump_to_tab(&mut app, '3');
+    press(&mut app, KeyCode::Char('p'));
+    assert_eq!(tabs(&app), ["3.12.4", "system", "3.11.7"]);
+    assert_eq!(app.versions.selected(), Some(0));
+
+    // An unpinned tab can't move in front of a pinned one, nor the other way round
+    press(&mut app, KeyCode::Char('>'));
+    assert_eq!(tabs(&app), ["3.12.4", "system", "3.11.7"]);
+    jump_to_tab(&mut app, '3');
+    press(&mut app, KeyCode::Char('<'));
+    press(&mut app, KeyCode::Char('<'));
+    assert_eq!(tabs(&app), ["3.12.4", "3.11.7", "system"]);
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.11.7"));
+
+    // Rediscovering the versions keeps the user's order and adds new ones at the end
+    let discovered = ["system", "3.11.7", "3.12.4", "3.13.0"].map(String::from).to_vec();
+    app.handle_event(Event::VersionsChanged(Ok(discovered)));
+    assert_eq!(tabs(&app), ["3.12.4", "3.11.7", "system", "3.13.0"]);
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.11.7"));
+
+    jump_to_tab(&mut app, '1');
+    press(&mut app, KeyCode::Char('p'));
+    assert!(app.pinned.is_empty());
+    assert_eq!(tabs(&app), ["3.12.4", "3.11.7", "system", "3.13.0"]);
+}
+
+#[test]
+fn clicking_a_tab_opens_its_page() {
+    let mut app = sample_app();
+    let (index, x, _) = ui::tab_positions(&app, 80)[1];
+    let click = MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column: x + 1, row: 0, modifiers: KeyModifiers::NONE };
+    app.handle_event(Event::Input(TermEvent::Mouse(click)));
+    assert_eq!(app.versions.selected(), Some(index));
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.11.7"));
+}
+
+#[test]
+fn the_selected_tab_scrolls_into_a_narrow_bar() {
+    let versions: Vec<String> = (0..12).map(|i| format!("3.{}.0", i)).collect();
+    let mut app = App::new(versions, HashMap::new(), String::new(), "Pyenv".to_string(), String::new());
+    assert_eq!(ui::tab_positions(&app, 40).first().map(|tab| tab.0), Some(0));
+    app.versions.select(11);
+    let positions = ui::tab_positions(&app, 40);
+    assert!(positions.iter().any(|tab| tab.0 == 11));
+    assert!(positions.iter().all(|&(_, x, width)| x + width <= 40));
+}
+
+fn load_packages(app: &mut App, version: &str, count: usize) {
+    app.handle_event(Event::PackagesLoaded {
+        version: version.to_string(),
+        inventory: Inventory {
+            packages: (1..=count).map(|i| format!("package-{:02} 1.0", i)).collect(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    });
+}
+
+fn mouse(app: &mut App, kind: MouseEventKind, column: u16, row: u16) -> Vec<Task> {
+    app.handle_event(Event::Input(TermEvent::Mouse(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE })))
+}
+
+#[test]
+fn the_pages_packages_scroll_and_stay_focused_across_tabs() {
+    let mut app = sample_app();
+    load_packages(&mut app, "system", 40);
+    load_packages(&mut app, "3.11.7", 5);
+    let areas = app.areas();
+    let rows = areas.packages.height as usize - 2;
+
+    // The wheel scrolls the page without taking the keys from the versions
+    for _ in 0..rows + 3 {
+        mouse(&mut app, MouseEventKind::ScrollDown, areas.packages.x + 2, areas.packages.y + 2);
+    }
+    assert_eq!(app.installed.selected(), Some(rows + 3));
+    // As drawing the frame does
+    assert_eq!(app.installed.scroll(rows), 4);
+    assert!(!app.packages_focused);
+
+    // A click picks the row under the pointer and hands the page the keys
+    mouse(&mut app, MouseEventKind::Down(MouseButton::Left), areas.packages.x + 2, areas.packages.y + 1);
+    assert!(app.packages_focused);
+    assert_eq!(app.installed.selected(), Some(4));
+    press(&mut app, KeyCode::PageDown);
+    assert_eq!(app.installed.selected(), Some(4 + rows));
+
+    // The next tab opens on its first package, still with the page focused
+    press(&mut app, KeyCode::Char('l'));
+    assert_eq!(app.selected_version().map(String::as_str), Some("3.11.7"));
+    assert!(app.packages_focused);
+    assert_eq!(app.installed.selected(), Some(0));
+    press(&mut app, KeyCode::Char('G'));
+    assert_eq!(app.installed.selected_item().map(String::as_str), Some("package-05 1.0"));
+
+    // Clicking the versions gives them the keys back
+    mouse(&mut app, MouseEventKind::Down(MouseButton::Left), areas.versions.x + 2, areas.versions.y + 1);
+    assert!(!app.packages_focused);
+    assert_eq!(app.selected_version().map(String::as_str), Some("system"));
+}
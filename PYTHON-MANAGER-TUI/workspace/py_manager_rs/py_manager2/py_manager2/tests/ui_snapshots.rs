@@ -0,0 +1,332 @@
+// Renders each screen into tui's TestBackend and compares the text against the
+// files in tests/snapshots. Run with UPDATE_SNAPSHOTS=1 to accept a layout change.
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyEvent, KeyModifiers};
+use keymap::Action;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use tracing::Level;
+use tui::backend::TestBackend;
+use tui::Terminal;
+
+use py_manager2::app::{App, Toast};
+use py_manager2::config::Config;
+use py_manager2::event::Event;
+use py_manager2::logging::LogLine;
+use py_manager2::modal::{Job, Modal};
+use py_manager2::ui;
+use py_manager_core::cache::{Freshness, Inventory};
+use py_manager_core::site_packages::Interpreter;
+
+const SIZES: [(u16, u16); 3] = [(80, 24), (120, 40), (50, 16)];
+
+fn sample_app() -> App {
+    let versions = vec![
+        "system".to_string(),
+        "3.11.7".to_string(),
+        "3.12.4".to_string(),
+    ];
+    let mut package_cache = HashMap::new();
+    package_cache.insert(
+        "system".to_string(),
+        Inventory {
+            packages: vec!["pip      24.0".to_string(), "requests 2.32.3".to_string(), "six      1.16.0".to_string()],
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    );
+    package_cache.insert(
+        "3.11.7".to_string(),
+        Inventory {
+            packages: vec!["pip        23.2.1".to_string(), "setuptools 65.5.0".to_string()],
+            site_packages_fingerprint: Some(1),
+            freshness: Freshness::Cached,
+        },
+    );
+    let venv = "/home/me/.pyenv/versions/3.12.4".to_string();
+    App::new(versions, package_cache, "3.12.4".to_string(), "Pyenv".to_string(), venv)
+}
+
+fn render(app: &App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| ui::draw(f, app)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        let line: String = (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.as_str()).collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+fn assert_snapshot(name: &str, app: &App) {
+    for (width, height) in SIZES {
+        let actual = render(app, width, height);
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("snapshots")
+            .join(format!("{}_{}x{}.txt", name, width, height));
+
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing snapshot {}; run with UPDATE_SNAPSHOTS=1", path.display()));
+        assert!(
+            actual == expected,
+            "{} at {}x{} differs from {}\n--- expected\n{}--- actual\n{}",
+            name,
+            width,
+            height,
+            path.display(),
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn version_list_with_packages() {
+    let mut app = sample_app();
+    type_keys(&mut app, [KeyCode::Char('j')]);
+    assert_snapshot("version_list", &app);
+}
+
+#[test]
+fn packages_still_loading() {
+    let mut app = sample_app();
+    type_keys(&mut app, [KeyCode::Char('G')]);
+    assert_snapshot("packages_loading", &app);
+}
+
+#[test]
+fn packages_failed_to_read() {
+    let mut app = sample_app();
+    type_keys(&mut app, [KeyCode::Char('G')]);
+    app.package_errors.insert("3.12.4".to_string(), "`pyenv` was not found on PATH".to_string());
+    assert_snapshot("packages_error", &app);
+}
+
+#[test]
+fn options_popup() {
+    let mut app = sample_app();
+    type_keys(&mut app, [KeyCode::Char('j'), KeyCode::Enter, KeyCode::Down]);
+    assert_snapshot("popup", &app);
+}
+
+#[test]
+fn pypi_pane_while_searching() {
+    let mut app = sample_app();
+    app.show_pypi = true;
+    app.pypi_packages
+        .set_items(vec!["requests".to_string(), "requests-oauthlib".to_string(), "requests-toolbelt".to_string()]);
+    app.pypi_packages.select(1);
+    type_keys(&mut app, "areque".chars().map(KeyCode::Char));
+    assert_snapshot("pypi", &app);
+}
+
+#[test]
+fn available_versions_with_the_installed_ones_dimmed() {
+    let mut app = sample_app();
+    type_keys(&mut app, [KeyCode::Char('i')]);
+    assert_snapshot("available_loading", &app);
+
+    let versions = ["3.13.0", "3.12.4", "3.11.9", "3.11.7", "Anaconda3-2024.02", "3.10.14"];
+    app.handle_event(Event::AvailableVersions(Ok(versions.iter().map(|v| v.to_string()).collect())));
+    type_keys(&mut app, "z/3.11".chars().map(KeyCode::Char).chain([KeyCode::Enter, KeyCode::Char('n')]));
+    assert_snapshot("available", &app);
+}
+
+#[test]
+fn progress_then_error_status() {
+    let mut app = sample_app();
+    app.modals.push(Modal::Progress {
+        title: "Installing".to_string(),
+        message: "Installing requests".to_string(),
+        job: Job::Install,
+    });
+    app.error_message = Some("Searching PyPI failed: package index error: timed out".to_string());
+    assert_snapshot("progress", &app);
+
+    app.modals.clear();
+    assert_snapshot("status_error", &app);
+}
+
+#[test]
+fn no_versions_found() {
+    let app = App::new(Vec::new(), HashMap::new(), String::new(), "None".to_string(), "Unknown".to_string());
+    assert_snapshot("empty", &app);
+}
+
+#[test]
+fn log_panel_filtered_to_warnings() {
+    let mut app = sample_app();
+    for (time, level, message) in [
+        ("12:00:01", Level::INFO, "starting"),
+        ("12:00:02", Level::WARN, "failed to watch dir=/tmp/gone error=No such file or directory"),
+        ("12:00:03", Level::DEBUG, "command finished duration_ms=12 exit_code=0"),
+        ("12:00:04", Level::ERROR, "Switching to 3.12.4 failed: `pyenv` was not found on PATH"),
+    ] {
+        app.handle_event(Event::Log(LogLine { time: time.to_string(), level, message: message.to_string() }));
+    }
+    app.show_log = true;
+    assert_snapshot("log_all", &app);
+
+    // From trace round to warn
+    type_keys(&mut app, "fff".chars().map(KeyCode::Char));
+    assert_eq!(app.log_filter, Level::WARN);
+    assert_snapshot("log_warn", &app);
+}
+
+#[test]
+fn toasts_stack_in_the_corner() {
+    let mut app = sample_app();
+    for (level, message) in [(Level::INFO, "Switched to 3.12.4"), (Level::INFO, "Installed requests")] {
+        app.toasts.push(Toast { level, message: message.to_string(), expires: Instant::now() });
+    }
+    assert_snapshot("toasts", &app);
+}
+
+#[test]
+fn help_for_the_package_list() {
+    let mut app = sample_app();
+    app.show_pypi = true;
+    app.pypi_packages.set_items(vec!["requests".to_string()]);
+    type_keys(&mut app, [KeyCode::Char('?')]);
+    assert_snapshot("help_pypi", &app);
+
+    // Past the bottom of a short screen the lines scroll under a scrollbar
+    type_keys(&mut app, [KeyCode::Char('G')]);
+    assert_snapshot("help_pypi_end", &app);
+}
+
+fn type_keys(app: &mut App, codes: impl IntoIterator<Item = KeyCode>) {
+    for code in codes {
+        app.handle_event(Event::Input(TermEvent::Key(KeyEvent::new(code, KeyModifiers::NONE))));
+    }
+}
+
+#[test]
+fn package_diff_under_an_ambiguous_completion() {
+    let mut app = sample_app();
+    type_keys(&mut app, ":diff system 3.11".chars().map(KeyCode::Char).chain([KeyCode::Enter]));
+    type_keys(&mut app, ":switch 3.1".chars().map(KeyCode::Char).chain([KeyCode::Tab]));
+    assert!(app.error_message.is_none(), "{:?}", app.error_message);
+    assert_snapshot("diff_command", &app);
+}
+
+#[test]
+fn hints_follow_rebound_keys() {
+    let config = Config::parse("[keys]\ndown = [\"t\", \"down\"]\n").unwrap();
+    let app = sample_app().with_config(config);
+    let down = app.hints().into_iter().find(|hint| hint.action == Some(Action::Down)).unwrap();
+    assert_eq!(down.keys, "t/down");
+}
+
+#[test]
+fn the_log_title_names_the_bound_keys() {
+    let config = Config::parse("[keys]\ncycle-log-filter = [\"v\"]\ntoggle-log = [\"ctrl-o\", \"O\"]\n").unwrap();
+    let mut app = sample_app().with_config(config);
+    app.show_log = true;
+    let text = render(&app, 120, 40);
+    assert!(text.contains("Log (level trace and above; v: filter, ctrl-o/O: close)"), "{}", text);
+}
+
+#[test]
+fn long_lists_scroll_with_a_scrollbar() {
+    let mut app = sample_app();
+    app.show_pypi = true;
+    app.pypi_packages.set_items((1..=40).map(|i| format!("package-{:02}", i)).collect());
+    app.pypi_packages.select(24);
+    assert_snapshot("pypi_scrolled", &app);
+}
+
+#[test]
+fn installed_packages_scroll_with_a_cursor() {
+    let mut app = sample_app();
+    app.package_cache.insert(
+        "system".to_string(),
+        Inventory {
+            packages: (1..=30).map(|i| format!("package-{:02} 1.0.{}", i, i)).collect(),
+            site_packages_fingerprint: None,
+            freshness: Freshness::Fresh,
+        },
+    );
+    type_keys(&mut app, [KeyCode::Tab, KeyCode::Char('G')]);
+    assert_snapshot("installed_scrolled", &app);
+}
+
+#[test]
+fn searched_and_filtered_lists() {
+    let mut app = sample_app();
+    type_keys(&mut app, "/3.1".chars().map(KeyCode::Char));
+    assert_snapshot("search_typing", &app);
+
+    type_keys(&mut app, [KeyCode::Enter, KeyCode::Char('g'), KeyCode::Char('g'), KeyCode::Tab]);
+    type_keys(&mut app, "/s".chars().map(KeyCode::Char).chain([KeyCode::Enter, KeyCode::Char('F')]));
+    assert_snapshot("search_filtered", &app);
+}
+
+#[test]
+fn an_empty_search_result_has_nothing_to_select() {
+    let mut app = sample_app();
+    app.show_pypi = true;
+    type_keys(&mut app, [KeyCode::Down, KeyCode::PageDown, KeyCode::Up, KeyCode::PageUp]);
+    assert_eq!(app.pypi_packages.selected(), None);
+    let enter = app.handle_event(Event::Input(TermEvent::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))));
+    assert!(enter.is_empty());
+    assert_snapshot("pypi_empty", &app);
+}
+
+#[test]
+fn interpreter_page_with_a_pinned_tab() {
+    let mut app = sample_app();
+    let alt = |digit| Event::Input(TermEvent::Key(KeyEvent::new(KeyCode::Char(digit), KeyModifiers::ALT)));
+    app.handle_event(alt('3'));
+    type_keys(&mut app, [KeyCode::Char('p')]);
+    app.handle_event(alt('3'));
+    app.interpreters.insert(
+        "3.11.7".to_string(),
+        Interpreter {
+            prefix: Some(PathBuf::from("/home/me/.pyenv/versions/3.11.7")),
+            executable: None,
+            site_packages: vec![PathBuf::from("/home/me/.pyenv/versions/3.11.7/lib/python3.11/site-packages")],
+        },
+    );
+    assert_snapshot("tabs_pinned", &app);
+}
+
+#[test]
+fn stacked_and_zoomed_panes() {
+    let mut app = sample_app();
+    type_keys(&mut app, [KeyCode::Char('|'), KeyCode::Char('-'), KeyCode::Char('-')]);
+    assert_snapshot("layout_stacked", &app);
+
+    app.handle_event(Event::Input(TermEvent::Key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL))));
+    type_keys(&mut app, [KeyCode::Char('z')]);
+    assert_snapshot("layout_zoomed_log", &app);
+}
+
+#[test]
+fn a_tiny_terminal_gets_a_note_instead_of_panes() {
+    let app = sample_app();
+    let text = render(&app, 24, 8);
+    assert!(text.contains("Terminal too small"), "{}", text);
+    assert!(text.contains("24x8, needs 30x10"), "{}", text);
+    assert!(!text.contains("Status"), "{}", text);
+}
+
+#[test]
+fn long_rows_end_in_an_ellipsis_and_keep_their_version() {
+    assert_eq!(ui::ellipsize("3.12.4 (set by /home/me/.pyenv/version)", 8), "3.12.4 …");
+    assert_eq!(ui::ellipsize("system", 6), "system");
+    assert_eq!(ui::fit_package("typing_extensions 4.12.2", 16), "typing_e… 4.12.2");
+    assert_eq!(ui::fit_package("pip        24.0", 20), "pip        24.0");
+    assert_eq!(ui::fit_package("pip 24.0", 5), "pip …");
+}
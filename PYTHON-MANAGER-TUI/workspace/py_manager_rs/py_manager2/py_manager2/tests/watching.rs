@@ -0,0 +1,51 @@
+// What the app asks for when the file watcher reports a change: site-packages
+// changes are re-read outright, even when a refresh is already running.
+
+mod common;
+
+use py_manager2::app::Task;
+use py_manager2::event::Event;
+use py_manager2::packages::Known;
+use py_manager2::watch::FsChange;
+use py_manager_core::cache::{Freshness, Inventory};
+
+use common::{sample_app, type_keys};
+
+fn inventory(fingerprint: i64) -> Inventory {
+    Inventory {
+        packages: vec!["pip 24.0".to_string()],
+        site_packages_fingerprint: Some(fingerprint),
+        freshness: Freshness::Fresh,
+    }
+}
+
+fn site_packages_changed(version: &str) -> Event {
+    Event::FsChanged(FsChange::SitePackages(version.to_string()))
+}
+
+#[test]
+fn a_watched_change_skips_the_fingerprint_check() {
+    let mut app = sample_app();
+    app.package_cache.insert("3.12.4".to_string(), inventory(1));
+
+    let tasks = app.handle_event(site_packages_changed("3.12.4"));
+    assert_eq!(tasks, vec![Task::RefreshPackages { version: "3.12.4".to_string(), known: None }]);
+}
+
+#[test]
+fn a_change_during_a_refresh_is_forced_once_it_ends() {
+    let mut app = sample_app();
+    app.package_cache.insert("3.12.4".to_string(), inventory(1));
+    app.handle_event(site_packages_changed("3.12.4"));
+    assert!(app.handle_event(site_packages_changed("3.12.4")).is_empty());
+
+    let loaded = Event::PackagesLoaded { version: "3.12.4".to_string(), inventory: inventory(2) };
+    assert_eq!(app.handle_event(loaded), vec![Task::RefreshPackages { version: "3.12.4".to_string(), known: None }]);
+
+    // Selecting a version still trusts an unchanged fingerprint
+    let unchanged = app.handle_event(Event::PackagesUnchanged("3.12.4".to_string()));
+    assert!(unchanged.is_empty());
+    let tasks = type_keys(&mut app, "G");
+    let known = Known { site_packages_fingerprint: Some(2), freshness: Freshness::Fresh };
+    assert!(tasks.contains(&Task::RefreshPackages { version: "3.12.4".to_string(), known: Some(known) }), "{:?}", tasks);
+}
@@ -0,0 +1,138 @@
+// The `/` search of every list in py_manager2: a query matched fuzzily against
+// each row, `n`/`N` to step between the matches and a filter mode that hides the
+// rows that don't match. Like the navigation crate it knows nothing about how a
+// list is stored or drawn; frontends hand it the rows' text.
+//
+// The query's characters have to appear in the row in order, ignoring case and
+// spaces, so "312" finds "3.12.4". A row that contains the query as typed is
+// highlighted there rather than wherever the first scattered match happens to fall.
+
+// One list's query and whether the rows that don't match it are hidden
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Search {
+    pub query: String,
+    pub filter: bool,
+}
+
+impl Search {
+    pub fn is_empty(&self) -> bool {
+        self.query.is_empty()
+    }
+
+    // Rows are hidden only while there is something to match them against
+    pub fn filtering(&self) -> bool {
+        self.filter && !self.query.is_empty()
+    }
+
+    // Positions (in chars) of `text` that the query matched, or None when it
+    // doesn't match or there is no query
+    pub fn positions(&self, text: &str) -> Option<Vec<usize>> {
+        if self.query.is_empty() {
+            return None;
+        }
+        fuzzy_match(&self.query, text)
+    }
+
+    // Indices of the rows matching the query, in order
+    pub fn matches<'a>(&self, rows: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+        if self.query.is_empty() {
+            return Vec::new();
+        }
+        rows.into_iter()
+            .enumerate()
+            .filter(|(_, text)| fuzzy_match(&self.query, text).is_some())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.filter = false;
+    }
+}
+
+// The match to go to from row `from` (from the first match when None), wrapping
+// around at either end; `from` itself counts when `inclusive`
+pub fn next_match(matches: &[usize], from: Option<usize>, forward: bool, inclusive: bool) -> Option<usize> {
+    let found = match (from, forward) {
+        (None, _) => matches.first(),
+        (Some(from), true) => matches.iter().find(|&&i| i > from || (inclusive && i == from)).or_else(|| matches.first()),
+        (Some(from), false) => matches.iter().rev().find(|&&i| i < from || (inclusive && i == from)).or_else(|| matches.last()),
+    };
+    found.copied()
+}
+
+// Positions (in chars) of `text` that `query` matched, or None when it doesn't
+pub fn fuzzy_match(query: &str, text: &str) -> Option<Vec<usize>> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).map(lower).collect();
+    let text: Vec<char> = text.chars().map(lower).collect();
+    if query.is_empty() {
+        return Some(Vec::new());
+    }
+
+    if let Some(start) = text.windows(query.len()).position(|window| window == query.as_slice()) {
+        return Some((start..start + query.len()).collect());
+    }
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut wanted = query.iter().peekable();
+    for (i, c) in text.iter().enumerate() {
+        match wanted.peek() {
+            Some(&w) if w == c => {
+                positions.push(i);
+                wanted.next();
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+    wanted.peek().is_none().then_some(positions)
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters_match_in_order() {
+        assert_eq!(fuzzy_match("312", "3.12.4"), Some(vec![0, 2, 3]));
+        assert_eq!(fuzzy_match("213", "3.12.4"), None);
+        assert_eq!(fuzzy_match("", "3.12.4"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn contiguous_matches_win_and_case_is_ignored() {
+        assert_eq!(fuzzy_match("12", "3.11.12"), Some(vec![5, 6]));
+        assert_eq!(fuzzy_match("Req", "requests 2.32.3"), Some(vec![0, 1, 2]));
+        assert_eq!(fuzzy_match("py py", "pypy3.10"), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn an_empty_query_matches_nothing_and_filters_nothing() {
+        let mut search = Search { query: String::new(), filter: true };
+        assert_eq!(search.matches(["3.11.7", "3.12.4"]), Vec::<usize>::new());
+        assert_eq!(search.positions("3.12.4"), None);
+        assert!(!search.filtering());
+        search.query.push_str("12");
+        assert_eq!(search.matches(["3.11.7", "3.12.4", "system"]), vec![1]);
+        assert!(search.filtering());
+        search.clear();
+        assert_eq!(search, Search::default());
+    }
+
+    #[test]
+    fn stepping_between_matches_wraps_around() {
+        let matches = [2, 5, 9];
+        assert_eq!(next_match(&matches, None, true, false), Some(2));
+        assert_eq!(next_match(&matches, Some(5), true, false), Some(9));
+        assert_eq!(next_match(&matches, Some(5), true, true), Some(5));
+        assert_eq!(next_match(&matches, Some(9), true, false), Some(2));
+        assert_eq!(next_match(&matches, Some(2), false, false), Some(9));
+        assert_eq!(next_match(&matches, Some(6), false, false), Some(5));
+        assert_eq!(next_match(&[], Some(6), true, true), None);
+    }
+}
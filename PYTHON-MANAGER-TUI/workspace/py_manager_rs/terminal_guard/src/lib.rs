@@ -0,0 +1,168 @@
+// Puts the terminal into raw mode on the alternate screen and guarantees it comes
+// back out again: on drop, on a panic in any thread, on SIGINT/SIGTERM, and around
+// a Ctrl-Z suspend.
+//
+// An app with an event loop registers a handler with `on_request`; signals and
+// panics off the main thread then reach it as requests to quit or suspend, so
+// only its own thread touches the terminal and it can save its state on the way
+// out. Without a handler a signal exits or suspends the process on the spot.
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use signal_hook::consts::{SIGINT, SIGSTOP, SIGTERM, SIGTSTP};
+use signal_hook::iterator::Signals;
+use std::io;
+use std::panic;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+use std::thread::{self, ThreadId};
+
+// Whether the terminal is currently in our raw/alternate-screen state; swapped
+// off by whichever restore gets there first
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static MOUSE: AtomicBool = AtomicBool::new(false);
+// The thread that entered the screen; a panic there unwinds through the guard
+static OWNER: OnceLock<ThreadId> = OnceLock::new();
+static ON_REQUEST: Mutex<Option<Handler>> = Mutex::new(None);
+static INSTALL: Once = Once::new();
+
+type Handler = Box<dyn Fn(Request) + Send>;
+
+// What a signal or a panic elsewhere asks of the app's event loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    // SIGINT, SIGTERM, or a panic in another thread: save and exit
+    Quit,
+    // SIGTSTP: call `TerminalGuard::suspend` from the loop's own thread
+    Suspend,
+}
+
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    // Enter raw mode and the alternate screen, optionally capturing the mouse
+    pub fn enter(mouse: bool) -> io::Result<TerminalGuard> {
+        MOUSE.store(mouse, Ordering::SeqCst);
+        install_handlers()?;
+        enter_screen()?;
+        Ok(TerminalGuard { _private: () })
+    }
+
+    // Called from the signal thread, or from a panicking thread, instead of
+    // exiting or suspending there; the callback should only pass the request on
+    pub fn on_request<F: Fn(Request) + Send + 'static>(&self, callback: F) {
+        *ON_REQUEST.lock().unwrap() = Some(Box::new(callback));
+    }
+
+    // Hand the terminal back to the shell and stop, as Ctrl-Z would outside raw
+    // mode. Returns once the job is resumed, with the screen re-entered; the
+    // caller should clear and redraw.
+    pub fn suspend(&self) -> io::Result<()> {
+        suspend_process()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// False once the screen was given back, e.g. by a panic in another thread; there
+// is nothing left to draw on then
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+// Leave raw mode and the alternate screen; safe to call any number of times, from
+// any thread
+pub fn restore() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        let mut stdout = io::stdout();
+        if MOUSE.load(Ordering::SeqCst) {
+            let _ = execute!(stdout, DisableMouseCapture);
+        }
+        let _ = execute!(stdout, LeaveAlternateScreen, Show);
+        let _ = disable_raw_mode();
+    }
+}
+
+fn enter_screen() -> io::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    if MOUSE.load(Ordering::SeqCst) {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    ACTIVE.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+fn suspend_process() -> io::Result<()> {
+    let was_active = ACTIVE.load(Ordering::SeqCst);
+    restore();
+    signal_hook::low_level::raise(SIGSTOP)?;
+    if was_active {
+        enter_screen()?;
+    }
+    Ok(())
+}
+
+// Hand `request` to the app's handler; false when there is none
+fn send(request: Request) -> bool {
+    // A panic while the handler runs must not deadlock the hook on the lock
+    match ON_REQUEST.try_lock() {
+        Ok(callback) => match callback.as_ref() {
+            Some(callback) => {
+                callback(request);
+                true
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
+
+// The panic hook and the signal thread are process-wide, so install them once
+fn install_handlers() -> io::Result<()> {
+    let mut result = Ok(());
+    INSTALL.call_once(|| {
+        // Restore first so the panic message lands on the normal screen, readable.
+        // A panic in another thread, e.g. a tokio task, leaves the app running
+        // without a screen, so it is asked to quit as well.
+        let _ = OWNER.set(thread::current().id());
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            previous(info);
+            if OWNER.get() != Some(&thread::current().id()) {
+                send(Request::Quit);
+            }
+        }));
+
+        result = Signals::new([SIGINT, SIGTERM, SIGTSTP]).map(|mut signals| {
+            thread::spawn(move || {
+                for signal in signals.forever() {
+                    let request = if signal == SIGTSTP { Request::Suspend } else { Request::Quit };
+                    if send(request) {
+                        continue;
+                    }
+                    if signal == SIGTSTP {
+                        let _ = suspend_process();
+                    } else {
+                        restore();
+                        process::exit(128 + signal);
+                    }
+                }
+            });
+        });
+    });
+    result
+}
@@ -0,0 +1,370 @@
+// Colors for py_manager2. The app asks for a role (`selected`, `active`, ...)
+// instead of naming colors, and its config reads the theme from
+// $XDG_CONFIG_HOME/py_manager/config.toml and hands it to `Theme::resolve`:
+//
+//     [theme]
+//     name = "light"
+//
+//     [themes.solarized]
+//     base = "dark"
+//     selected = "#b58900"
+//     active = "cyan"
+//
+// The built-in themes are dark (the default), light and high-contrast; a theme
+// of your own starts from `base` and replaces the roles it lists. Setting
+// NO_COLOR drops every color but keeps bold, reversed and underlined text, and
+// the app marks the selection and the active version with `>` and `*` so
+// nothing depends on color alone.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use tui::style::{Color, Modifier, Style};
+
+pub const BUILT_IN: [&str; 3] = ["dark", "light", "high-contrast"];
+
+// The `[theme]` table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeChoice {
+    // A built-in theme or one from `[themes]`
+    pub name: String,
+}
+
+impl Default for ThemeChoice {
+    fn default() -> ThemeChoice {
+        ThemeChoice { name: String::from("dark") }
+    }
+}
+
+// One `[themes.<name>]` table: colors by role, as written in the config. Every
+// key but `base` is a role, checked when the theme is resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSpec {
+    // Built-in theme the unlisted roles come from; dark when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+// The `[themes]` table: theme name -> colors
+pub type ThemeTable = BTreeMap<String, ThemeSpec>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    // Plain text and the screen behind it; Reset leaves the terminal's own
+    pub text: Color,
+    pub background: Color,
+    // Borders and titles of lists
+    pub accent: Color,
+    // The row or tab under the cursor
+    pub selected: Color,
+    // The Python version in use
+    pub active: Color,
+    // Secondary text, like packages that are already installed
+    pub muted: Color,
+    pub error: Color,
+    pub warning: Color,
+    pub success: Color,
+}
+
+const ROLES: [&str; 9] = ["text", "background", "accent", "selected", "active", "muted", "error", "warning", "success"];
+
+impl Palette {
+    fn built_in(name: &str) -> Option<Palette> {
+        let palette = match name {
+            "dark" => Palette {
+                text: Color::Reset,
+                background: Color::Reset,
+                accent: Color::Cyan,
+                selected: Color::Yellow,
+                active: Color::Magenta,
+                muted: Color::DarkGray,
+                error: Color::Red,
+                warning: Color::Yellow,
+                success: Color::Green,
+            },
+            // Yellow and light gray are unreadable on white
+            "light" => Palette {
+                text: Color::Reset,
+                background: Color::Reset,
+                accent: Color::Blue,
+                selected: Color::Magenta,
+                active: Color::Green,
+                muted: Color::DarkGray,
+                error: Color::Red,
+                warning: Color::Indexed(130),
+                success: Color::Green,
+            },
+            // Bright colors on black, whatever the terminal's own colors are
+            "high-contrast" => Palette {
+                text: Color::White,
+                background: Color::Black,
+                accent: Color::LightCyan,
+                selected: Color::LightYellow,
+                active: Color::LightGreen,
+                muted: Color::White,
+                error: Color::LightRed,
+                warning: Color::LightYellow,
+                success: Color::LightGreen,
+            },
+            _ => return None,
+        };
+        Some(palette)
+    }
+
+    fn role_mut(&mut self, role: &str) -> Option<&mut Color> {
+        Some(match role {
+            "text" => &mut self.text,
+            "background" => &mut self.background,
+            "accent" => &mut self.accent,
+            "selected" => &mut self.selected,
+            "active" => &mut self.active,
+            "muted" => &mut self.muted,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "success" => &mut self.success,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    palette: Palette,
+    // NO_COLOR: every role is the terminal's default color
+    plain: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::resolve(&ThemeChoice::default(), &ThemeTable::new()).expect("the default theme is built in")
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    // `[theme] name` is neither built in nor in `[themes]`
+    UnknownTheme(String),
+    // A `base` that isn't a built-in theme
+    UnknownBase { theme: String, base: String },
+    UnknownRole { theme: String, role: String },
+    InvalidColor { theme: String, role: String, color: String },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::UnknownTheme(name) => {
+                write!(f, "unknown theme `{}`; expected {} or one from [themes]", name, BUILT_IN.join(", "))
+            }
+            ThemeError::UnknownBase { theme, base } => {
+                write!(f, "themes.{}.base: unknown theme `{}`; expected one of {}", theme, base, BUILT_IN.join(", "))
+            }
+            ThemeError::UnknownRole { theme, role } => {
+                write!(f, "themes.{}: unknown role `{}`; expected one of {}", theme, role, ROLES.join(", "))
+            }
+            ThemeError::InvalidColor { theme, role, color } => write!(
+                f,
+                "themes.{}.{}: invalid color `{}`; use a name like \"cyan\", \"#rrggbb\" or 0-255",
+                theme, role, color
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+const NAMED_COLORS: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("gray", Color::Gray),
+    ("darkgray", Color::DarkGray),
+    ("lightred", Color::LightRed),
+    ("lightgreen", Color::LightGreen),
+    ("lightyellow", Color::LightYellow),
+    ("lightblue", Color::LightBlue),
+    ("lightmagenta", Color::LightMagenta),
+    ("lightcyan", Color::LightCyan),
+    ("white", Color::White),
+];
+
+// "cyan", "dark-gray", "#b58900" or a 256-color index
+pub fn parse_color(text: &str) -> Option<Color> {
+    let name = text.to_ascii_lowercase().replace(['-', '_'], "");
+    if let Some((_, color)) = NAMED_COLORS.iter().find(|(named, _)| *named == name) {
+        return Some(*color);
+    }
+    if let Some(hex) = text.strip_prefix('#').filter(|hex| hex.len() == 6) {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    text.parse::<u8>().ok().map(Color::Indexed)
+}
+
+// NO_COLOR set to anything but the empty string, per no-color.org
+pub fn no_color_requested() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+impl Theme {
+    // The theme `choice` names, built in or from `custom`
+    pub fn resolve(choice: &ThemeChoice, custom: &ThemeTable) -> Result<Theme, ThemeError> {
+        let name = choice.name.as_str();
+        let palette = match custom.get(name) {
+            Some(spec) => Theme::custom(name, spec)?,
+            None => Palette::built_in(name).ok_or_else(|| ThemeError::UnknownTheme(name.to_string()))?,
+        };
+        Ok(Theme { name: name.to_string(), palette, plain: false })
+    }
+
+    fn custom(name: &str, spec: &ThemeSpec) -> Result<Palette, ThemeError> {
+        let base = spec.base.as_deref().unwrap_or("dark");
+        let mut palette = Palette::built_in(base)
+            .ok_or_else(|| ThemeError::UnknownBase { theme: name.to_string(), base: base.to_string() })?;
+        for (role, color) in &spec.colors {
+            let slot = palette
+                .role_mut(role)
+                .ok_or_else(|| ThemeError::UnknownRole { theme: name.to_string(), role: role.clone() })?;
+            *slot = parse_color(color).ok_or_else(|| ThemeError::InvalidColor {
+                theme: name.to_string(),
+                role: role.clone(),
+                color: color.clone(),
+            })?;
+        }
+        Ok(palette)
+    }
+
+    // Check every theme in `custom`, not just the chosen one, so a typo shows up
+    // before it's switched to
+    pub fn validate(choice: &ThemeChoice, custom: &ThemeTable) -> Result<(), ThemeError> {
+        for (name, spec) in custom {
+            Theme::custom(name, spec)?;
+        }
+        Theme::resolve(choice, custom).map(|_| ())
+    }
+
+    // The same theme without any color, for NO_COLOR
+    pub fn without_color(self) -> Theme {
+        Theme { plain: true, ..self }
+    }
+
+    fn fg(&self, color: Color) -> Style {
+        if self.plain {
+            Style::default()
+        } else {
+            Style::default().fg(color)
+        }
+    }
+
+    // Text on the theme's background; drawn under everything else
+    pub fn base(&self) -> Style {
+        if self.plain {
+            return Style::default();
+        }
+        Style::default().fg(self.palette.text).bg(self.palette.background)
+    }
+
+    pub fn accent(&self) -> Style {
+        self.fg(self.palette.accent)
+    }
+
+    pub fn selected(&self) -> Style {
+        self.fg(self.palette.selected).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn active(&self) -> Style {
+        self.fg(self.palette.active).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn muted(&self) -> Style {
+        self.fg(self.palette.muted)
+    }
+
+    pub fn error(&self) -> Style {
+        self.fg(self.palette.error)
+    }
+
+    pub fn warning(&self) -> Style {
+        self.fg(self.palette.warning)
+    }
+
+    pub fn success(&self) -> Style {
+        self.fg(self.palette.success)
+    }
+
+    // The selected tab: reversed, so it stands out without color too
+    pub fn tab(&self) -> Style {
+        self.accent().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choose(name: &str) -> ThemeChoice {
+        ThemeChoice { name: name.to_string() }
+    }
+
+    fn parse_table(text: &str) -> ThemeTable {
+        #[derive(Deserialize)]
+        struct ConfigFile {
+            themes: ThemeTable,
+        }
+        toml::from_str::<ConfigFile>(text).unwrap().themes
+    }
+
+    #[test]
+    fn colors_parse_by_name_hex_and_index() {
+        assert_eq!(parse_color("cyan"), Some(Color::Cyan));
+        assert_eq!(parse_color("Dark-Gray"), Some(Color::DarkGray));
+        assert_eq!(parse_color("#b58900"), Some(Color::Rgb(0xb5, 0x89, 0x00)));
+        assert_eq!(parse_color("130"), Some(Color::Indexed(130)));
+        assert_eq!(parse_color("#b589"), None);
+        assert_eq!(parse_color("teal"), None);
+    }
+
+    #[test]
+    fn built_in_themes_resolve_and_unknown_names_are_rejected() {
+        for name in BUILT_IN {
+            assert_eq!(Theme::resolve(&choose(name), &ThemeTable::new()).unwrap().name, name);
+        }
+        let message = Theme::resolve(&choose("solarized"), &ThemeTable::new()).unwrap_err().to_string();
+        assert_eq!(message, "unknown theme `solarized`; expected dark, light, high-contrast or one from [themes]");
+    }
+
+    #[test]
+    fn custom_themes_replace_roles_of_their_base() {
+        let table = parse_table("[themes.mine]\nbase = \"light\"\nselected = \"#ff0000\"\n");
+        let theme = Theme::resolve(&choose("mine"), &table).unwrap();
+        assert_eq!(theme.selected(), Style::default().fg(Color::Rgb(255, 0, 0)).add_modifier(Modifier::BOLD));
+        assert_eq!(theme.accent(), Style::default().fg(Color::Blue));
+
+        let bad = parse_table("[themes.mine]\nselcted = \"red\"\n");
+        let message = Theme::validate(&choose("dark"), &bad).unwrap_err().to_string();
+        assert!(message.starts_with("themes.mine: unknown role `selcted`"), "{}", message);
+        let bad = parse_table("[themes.mine]\nactive = \"teal\"\n");
+        let message = Theme::validate(&choose("dark"), &bad).unwrap_err().to_string();
+        assert!(message.starts_with("themes.mine.active: invalid color `teal`"), "{}", message);
+    }
+
+    #[test]
+    fn without_color_only_modifiers_are_left() {
+        let theme = Theme::resolve(&choose("high-contrast"), &ThemeTable::new()).unwrap().without_color();
+        assert_eq!(theme.base(), Style::default());
+        assert_eq!(theme.error(), Style::default());
+        assert_eq!(theme.selected(), Style::default().add_modifier(Modifier::BOLD));
+        assert_eq!(theme.tab(), Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD));
+    }
+}